@@ -9,15 +9,44 @@ pub const POSITION_USER_VAULT_SEED: &[u8] = b"position_user_vault";
 pub const POSITION_MM_VAULT_SEED: &[u8] = b"position_mm_vault";
 pub const ASSET_CONFIG_SEED: &[u8] = b"asset_config";
 pub const POSITION_REQUEST_SEED: &[u8] = b"position_request";
+pub const REQUEST_ESCROW_SEED: &[u8] = b"request_escrow";
+pub const SETTLEMENT_PRICE_SEED: &[u8] = b"settlement_price";
+pub const ROLE_REGISTRY_SEED: &[u8] = b"role_registry";
+pub const PENDING_CHANGE_SEED: &[u8] = b"pending_change";
+pub const USER_ACCOUNT_SEED: &[u8] = b"user_account";
+
+// Pause flags (GlobalState.pause_flags and AssetConfig.pause_flags)
+pub const PAUSE_REQUESTS: u8 = 1 << 0;
+pub const PAUSE_CONFIRMS: u8 = 1 << 1;
+pub const PAUSE_DEPOSITS: u8 = 1 << 2;    // Global only, vaults also hold quote mints
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 3; // Global only, vaults also hold quote mints
+pub const PAUSE_QUOTING: u8 = 1 << 4;
+pub const PAUSE_SETTLEMENT: u8 = 1 << 5;  // Settlement and settlement price publishing
+pub const PAUSE_ALL: u8 = PAUSE_REQUESTS
+    | PAUSE_CONFIRMS
+    | PAUSE_DEPOSITS
+    | PAUSE_WITHDRAWALS
+    | PAUSE_QUOTING
+    | PAUSE_SETTLEMENT;
 
 // MM Confirmation Window (seconds)
 pub const MM_CONFIRMATION_WINDOW: i64 = 30;
 
 // Pyth parameters
-pub const PYTH_STALENESS_THRESHOLD: u64 = 60; // 60 seconds
+pub const TWAP_SAMPLE_BUCKETS: u32 = 12;      // Equal slices of the TWAP window, one sample each
+pub const MIN_TWAP_SAMPLES: u32 = 9;          // Filled slices required before a TWAP is usable
+
+// Delay after expiry before the super-admin can publish a stuck settlement price
+pub const SETTLEMENT_FALLBACK_DELAY_SECONDS: i64 = 86400;
 
 // Quote parameters
 pub const MAX_STRIKES_PER_QUOTE: usize = 10;
 
 // Basis points (10000 = 100%)
 pub const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+// Hard cap on the protocol fee charged on premiums (10%)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1000;
+
+// Upper bound on the parameter change timelock (30 days)
+pub const MAX_TIMELOCK_DELAY_SECONDS: i64 = 30 * 86400;
@@ -73,4 +73,127 @@ pub enum ErrorCode {
 
     #[msg("Only the market maker can confirm this request")]
     UnauthorizedConfirmation,
+
+    #[msg("Quoted strike has no remaining capacity for this contract size")]
+    QuoteCapacityExhausted,
+
+    #[msg("Collateral mint does not match the quote strategy")]
+    InvalidCollateralMint,
+
+    #[msg("Position vault does not belong to this position")]
+    InvalidPositionVault,
+
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+
+    #[msg("Oracle price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+
+    #[msg("Pyth price update is not fully verified")]
+    PriceNotVerified,
+
+    #[msg("Invalid confidence bound")]
+    InvalidConfidenceBound,
+
+    #[msg("Price was not published within the settlement window around expiry")]
+    PriceOutsideSettlementWindow,
+
+    #[msg("Invalid settlement window")]
+    InvalidSettlementWindow,
+
+    #[msg("Price samples must be submitted in increasing publish time order")]
+    PriceSampleOutOfOrder,
+
+    #[msg("Not enough price samples to compute the TWAP")]
+    InsufficientPriceSamples,
+
+    #[msg("Asset is not configured for TWAP settlement")]
+    NotTwapSettlement,
+
+    #[msg("Settlement price account missing for this asset's settlement mode")]
+    MissingSettlementPriceAccount,
+
+    #[msg("Settlement price for this expiry has already been published")]
+    SettlementPriceAlreadyPublished,
+
+    #[msg("Settlement price for this expiry has not been published yet")]
+    SettlementPriceNotPublished,
+
+    #[msg("Position has not been settled yet")]
+    PositionNotSettled,
+
+    #[msg("Position vault still holds tokens")]
+    PositionVaultNotEmpty,
+
+    #[msg("Protocol fee exceeds the maximum")]
+    ProtocolFeeTooHigh,
+
+    #[msg("Invalid treasury address")]
+    InvalidTreasury,
+
+    #[msg("Invalid maximum price age")]
+    InvalidPriceAge,
+
+    #[msg("Invalid pending owner")]
+    InvalidPendingOwner,
+
+    #[msg("No ownership transfer is pending")]
+    NoPendingTransfer,
+
+    #[msg("Invalid role holder")]
+    InvalidRoleHolder,
+
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+
+    #[msg("Queued change is not executable yet")]
+    ChangeNotReady,
+
+    #[msg("Queued change does not target this account")]
+    InvalidChangeTarget,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("This operation is paused for the asset")]
+    AssetPaused,
+
+    #[msg("Invalid quote mint")]
+    InvalidQuoteMint,
+
+    #[msg("Strike price outside the asset's band around spot")]
+    StrikeOutsideBand,
+
+    #[msg("Expiry outside the asset's allowed window")]
+    ExpiryOutsideWindow,
+
+    #[msg("Pyth price update account required")]
+    MissingPriceUpdate,
+
+    #[msg("Quote still has pending position requests")]
+    QuoteHasPendingRequests,
+
+    #[msg("Quote has not expired yet")]
+    QuoteNotExpired,
+
+    #[msg("Quote validity must be in the future and no later than the option expiry")]
+    InvalidQuoteValidity,
+
+    #[msg("Quote does not match the position request")]
+    QuoteMismatch,
+
+    #[msg("Token account is not the vault's token account")]
+    InvalidVaultTokenAccount,
+
+    #[msg("Premium vault must be passed for cash secured puts only")]
+    InvalidPremiumVault,
+
+    #[msg("This TWAP window slot already has a price sample")]
+    PriceBucketAlreadySampled,
+
+    #[msg("Settlement fallback is not open for this expiry yet")]
+    SettlementFallbackNotOpen,
+
+    #[msg("Asset still has open quotes or positions in its quote mint")]
+    AssetHasOpenExposure,
 }
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::{ParameterChange, PositionStatus};
+
+#[event]
+pub struct PositionConfirmed {
+    pub position: Pubkey,
+    pub request_id: u64,        // Request the position was opened from (now closed)
+    pub user: Pubkey,
+    pub market_maker: Pubkey,
+    pub premium: u64,           // Premium received by the user, net of fee
+    pub protocol_fee: u64,      // Fee sent to the treasury
+    pub premium_mint: Pubkey,
+}
+
+#[event]
+pub struct PositionClosed {
+    pub position: Pubkey,
+    pub position_id: u64,
+    pub user: Pubkey,
+    pub market_maker: Pubkey,
+    pub status: PositionStatus,
+    pub settlement_price: Option<u64>,
+    pub user_collateral_recipient: Pubkey, // Paid the user's collateral at settlement
+    pub user_collateral: u64,
+    pub mm_collateral_recipient: Pubkey,   // Paid the MM's collateral at settlement
+    pub mm_collateral: u64,
+    pub user_vault_swept: u64,  // Leftover swept from the user vault on close
+    pub mm_vault_swept: u64,    // Leftover swept from the MM vault on close
+}
+
+#[event]
+pub struct RequestCancelled {
+    pub request: Pubkey,
+    pub request_id: u64,
+    pub user: Pubkey,
+    pub market_maker: Pubkey,
+    pub quote: Pubkey,
+}
+
+#[event]
+pub struct ParameterChangeQueued {
+    pub target: Pubkey,
+    pub change: ParameterChange,
+    pub eta: i64,               // Earliest execution time
+}
+
+#[event]
+pub struct ParameterChangeExecuted {
+    pub target: Pubkey,
+    pub change: ParameterChange,
+}
+
+#[event]
+pub struct ParameterChangeCancelled {
+    pub target: Pubkey,
+}
@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
 use crate::state::*;
 use crate::constants::*;
 use crate::errors::ErrorCode;
+use crate::program::Solation;
 
-// Initialize global state
+// Initialize global state (program upgrade authority only)
 #[derive(Accounts)]
 pub struct InitializeGlobalState<'info> {
     #[account(
@@ -15,100 +17,301 @@ pub struct InitializeGlobalState<'info> {
     )]
     pub global_state: Account<'info, GlobalState>,
 
+    #[account(
+        init,
+        payer = authority,
+        space = RoleRegistry::LEN,
+        seeds = [ROLE_REGISTRY_SEED],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    /// CHECK: Treasury address
-    pub treasury: AccountInfo<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Solation>,
+
+    // Only the upgrade authority can claim the singleton global state
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
 
     pub system_program: Program<'info, System>,
 }
 
 pub fn handle_initialize_global_state(
     ctx: Context<InitializeGlobalState>,
-    protocol_fee_bps: u16,
+    config: GlobalStateConfig,
 ) -> Result<()> {
     let global_state = &mut ctx.accounts.global_state;
 
     global_state.authority = ctx.accounts.authority.key();
-    global_state.treasury = ctx.accounts.treasury.key();
-    global_state.protocol_fee_bps = protocol_fee_bps;
-    global_state.paused = false;
+    global_state.pending_authority = None;
+    global_state.treasury = config.treasury;
+    global_state.protocol_fee_bps = config.protocol_fee_bps;
+    global_state.max_protocol_fee_bps = config.max_protocol_fee_bps;
+    global_state.max_price_age_seconds = config.max_price_age_seconds;
+    global_state.timelock_delay_seconds = config.timelock_delay_seconds;
+    global_state.pause_flags = 0;
     global_state.total_volume = 0;
     global_state.total_positions = 0;
+    global_state.total_fees_collected = 0;
     global_state.bump = ctx.bumps.global_state;
 
+    global_state.validate()?;
+
+    // Every role starts with the super-admin until delegated
+    let role_registry = &mut ctx.accounts.role_registry;
+    role_registry.pauser = global_state.authority;
+    role_registry.asset_manager = global_state.authority;
+    role_registry.fee_manager = global_state.authority;
+    role_registry.keeper = global_state.authority;
+    role_registry.bump = ctx.bumps.role_registry;
+
     msg!("Global state initialized with authority: {}", global_state.authority);
 
     Ok(())
 }
 
-// Update global state
+// Assign an admin role (super-admin)
 #[derive(Accounts)]
-pub struct UpdateGlobalState<'info> {
+pub struct SetRole<'info> {
     #[account(
-        mut,
         seeds = [GLOBAL_STATE_SEED],
         bump = global_state.bump,
         has_one = authority @ ErrorCode::Unauthorized
     )]
     pub global_state: Account<'info, GlobalState>,
 
+    #[account(
+        mut,
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     pub authority: Signer<'info>,
 }
 
-pub fn handle_update_global_state(
-    ctx: Context<UpdateGlobalState>,
-    new_authority: Option<Pubkey>,
-    new_treasury: Option<Pubkey>,
-    new_fee_bps: Option<u16>,
-    paused: Option<bool>,
-) -> Result<()> {
+pub fn handle_set_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+    require!(holder != Pubkey::default(), ErrorCode::InvalidRoleHolder);
+
+    ctx.accounts.role_registry.set_holder(role, holder);
+
+    msg!("Role {:?} assigned to: {}", role, holder);
+
+    Ok(())
+}
+
+// Pause or unpause instruction classes protocol-wide (pauser, never timelocked)
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::Pauser) == pauser.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    pub pauser: Signer<'info>,
+}
+
+pub fn handle_set_pause_flags(ctx: Context<SetPauseFlags>, pause_flags: u8) -> Result<()> {
     let global_state = &mut ctx.accounts.global_state;
+    global_state.pause_flags = pause_flags;
+    global_state.validate()?;
 
-    if let Some(auth) = new_authority {
-        global_state.authority = auth;
-    }
+    msg!("Protocol pause flags set to: {:#08b}", pause_flags);
 
-    if let Some(treasury) = new_treasury {
-        global_state.treasury = treasury;
-    }
+    Ok(())
+}
 
-    if let Some(fee) = new_fee_bps {
-        global_state.protocol_fee_bps = fee;
-    }
+// Pause or unpause instruction classes for a single asset (pauser)
+#[derive(Accounts)]
+pub struct SetAssetPauseFlags<'info> {
+    #[account(
+        mut,
+        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
 
-    if let Some(pause) = paused {
-        global_state.paused = pause;
-    }
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::Pauser) == pauser.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
 
-    msg!("Global state updated");
+    pub pauser: Signer<'info>,
+}
+
+pub fn handle_set_asset_pause_flags(ctx: Context<SetAssetPauseFlags>, pause_flags: u8) -> Result<()> {
+    // Deposits and withdrawals can only be paused protocol-wide
+    require!(
+        pause_flags & !(PAUSE_REQUESTS | PAUSE_CONFIRMS | PAUSE_QUOTING | PAUSE_SETTLEMENT) == 0,
+        ErrorCode::InvalidPauseFlags
+    );
+
+    let asset_config = &mut ctx.accounts.asset_config;
+    asset_config.pause_flags = pause_flags;
+    asset_config.validate()?;
+
+    msg!(
+        "Asset {} pause flags set to: {:#08b}",
+        asset_config.asset_mint,
+        pause_flags
+    );
 
     Ok(())
 }
 
-// Add asset configuration
+// Disable an asset immediately (pauser); re-enabling goes through the timelock
 #[derive(Accounts)]
-#[instruction(asset_mint: Pubkey)]
-pub struct AddAsset<'info> {
+pub struct DisableAsset<'info> {
+    #[account(
+        mut,
+        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
     #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::Pauser) == pauser.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    pub pauser: Signer<'info>,
+}
+
+pub fn handle_disable_asset(ctx: Context<DisableAsset>) -> Result<()> {
+    let asset_config = &mut ctx.accounts.asset_config;
+    asset_config.enabled = false;
+    asset_config.validate()?;
+
+    msg!("Asset disabled: {}", asset_config.asset_mint);
+
+    Ok(())
+}
+
+// Propose a new admin (current authority)
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
         seeds = [GLOBAL_STATE_SEED],
         bump = global_state.bump,
         has_one = authority @ ErrorCode::Unauthorized
     )]
     pub global_state: Account<'info, GlobalState>,
 
+    pub authority: Signer<'info>,
+}
+
+pub fn handle_propose_authority(
+    ctx: Context<ProposeAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    require!(
+        new_authority != Pubkey::default() && new_authority != ctx.accounts.authority.key(),
+        ErrorCode::InvalidPendingOwner
+    );
+
+    ctx.accounts.global_state.pending_authority = Some(new_authority);
+
+    msg!("Authority transfer proposed to: {}", new_authority);
+
+    Ok(())
+}
+
+// Accept the admin role (pending authority)
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.pending_authority == Some(new_authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handle_accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let global_state = &mut ctx.accounts.global_state;
+
+    global_state.authority = ctx.accounts.new_authority.key();
+    global_state.pending_authority = None;
+
+    msg!("Authority transferred to: {}", global_state.authority);
+
+    Ok(())
+}
+
+// Cancel a pending admin transfer (current authority)
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = global_state.pending_authority.is_some() @ ErrorCode::NoPendingTransfer
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handle_cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    ctx.accounts.global_state.pending_authority = None;
+
+    msg!("Authority transfer cancelled");
+
+    Ok(())
+}
+
+// Add asset configuration (asset manager)
+#[derive(Accounts)]
+#[instruction(asset_mint: Pubkey, quote_mint: Pubkey)]
+pub struct AddAsset<'info> {
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::AssetManager) == asset_manager.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
     #[account(
         init,
-        payer = authority,
+        payer = asset_manager,
         space = AssetConfig::LEN,
         seeds = [ASSET_CONFIG_SEED, asset_mint.as_ref()],
         bump
     )]
     pub asset_config: Account<'info, AssetConfig>,
 
+    #[account(address = asset_mint)]
+    pub asset_mint_account: Account<'info, Mint>,
+
+    #[account(address = quote_mint)]
+    pub quote_mint_account: Account<'info, Mint>,
+
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub asset_manager: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
@@ -122,87 +325,33 @@ pub fn handle_add_asset(
     max_strike_percentage: u16,
     min_expiry_seconds: i64,
     max_expiry_seconds: i64,
-    decimals: u8,
+    max_confidence_bps: u16,
+    settlement_window_seconds: i64,
+    settlement_mode: SettlementMode,
 ) -> Result<()> {
-    require!(
-        min_strike_percentage < max_strike_percentage,
-        ErrorCode::InvalidStrikeRange
-    );
-
-    require!(
-        min_expiry_seconds < max_expiry_seconds,
-        ErrorCode::InvalidExpiryRange
-    );
-
     let asset_config = &mut ctx.accounts.asset_config;
 
     asset_config.asset_mint = asset_mint;
     asset_config.quote_mint = quote_mint;
     asset_config.pyth_feed_id = pyth_feed_id;
     asset_config.enabled = true;
+    asset_config.pause_flags = 0;
     asset_config.min_strike_percentage = min_strike_percentage;
     asset_config.max_strike_percentage = max_strike_percentage;
     asset_config.min_expiry_seconds = min_expiry_seconds;
     asset_config.max_expiry_seconds = max_expiry_seconds;
-    asset_config.decimals = decimals;
+    asset_config.decimals = ctx.accounts.asset_mint_account.decimals;
+    asset_config.quote_decimals = ctx.accounts.quote_mint_account.decimals;
+    asset_config.max_confidence_bps = max_confidence_bps;
+    asset_config.settlement_window_seconds = settlement_window_seconds;
+    asset_config.settlement_mode = settlement_mode;
+    asset_config.open_quotes = 0;
+    asset_config.open_positions = 0;
     asset_config.bump = ctx.bumps.asset_config;
 
-    msg!("Asset added: {}", asset_mint);
+    asset_config.validate()?;
 
-    Ok(())
-}
-
-// Update asset configuration
-#[derive(Accounts)]
-pub struct UpdateAsset<'info> {
-    #[account(
-        seeds = [GLOBAL_STATE_SEED],
-        bump = global_state.bump,
-        has_one = authority @ ErrorCode::Unauthorized
-    )]
-    pub global_state: Account<'info, GlobalState>,
-
-    #[account(
-        mut,
-        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
-        bump = asset_config.bump
-    )]
-    pub asset_config: Account<'info, AssetConfig>,
-
-    pub authority: Signer<'info>,
-}
-
-pub fn handle_update_asset(
-    ctx: Context<UpdateAsset>,
-    enabled: Option<bool>,
-    min_strike_percentage: Option<u16>,
-    max_strike_percentage: Option<u16>,
-    min_expiry_seconds: Option<i64>,
-    max_expiry_seconds: Option<i64>,
-) -> Result<()> {
-    let asset_config = &mut ctx.accounts.asset_config;
-
-    if let Some(e) = enabled {
-        asset_config.enabled = e;
-    }
-
-    if let Some(min) = min_strike_percentage {
-        asset_config.min_strike_percentage = min;
-    }
-
-    if let Some(max) = max_strike_percentage {
-        asset_config.max_strike_percentage = max;
-    }
-
-    if let Some(min) = min_expiry_seconds {
-        asset_config.min_expiry_seconds = min;
-    }
-
-    if let Some(max) = max_expiry_seconds {
-        asset_config.max_expiry_seconds = max;
-    }
-
-    msg!("Asset updated: {}", asset_config.asset_mint);
+    msg!("Asset added: {}", asset_mint);
 
     Ok(())
 }
@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use crate::state::*;
 use crate::constants::*;
 use crate::errors::ErrorCode;
+use crate::oracle;
 
 // Register as market maker
 #[derive(Accounts)]
@@ -26,10 +28,13 @@ pub fn handle_register_market_maker(ctx: Context<RegisterMarketMaker>) -> Result
     let market_maker = &mut ctx.accounts.market_maker;
 
     market_maker.owner = ctx.accounts.owner.key();
+    market_maker.original_owner = ctx.accounts.owner.key();
+    market_maker.pending_owner = None;
     market_maker.active = true;
     market_maker.total_positions = 0;
     market_maker.completed_positions = 0;
     market_maker.reputation_score = 100;
+    market_maker.next_quote_id = 0;
     market_maker.bump = ctx.bumps.market_maker;
 
     msg!("Market maker registered: {}", market_maker.owner);
@@ -42,8 +47,9 @@ pub fn handle_register_market_maker(ctx: Context<RegisterMarketMaker>) -> Result
 #[instruction(asset_mint: Pubkey)]
 pub struct InitializeVault<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, owner.key().as_ref()],
-        bump = market_maker.bump
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        has_one = owner @ ErrorCode::Unauthorized
     )]
     pub market_maker: Account<'info, MarketMaker>,
 
@@ -107,8 +113,16 @@ pub fn handle_initialize_vault(
 #[derive(Accounts)]
 pub struct DepositLiquidity<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, owner.key().as_ref()],
-        bump = market_maker.bump
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_DEPOSITS) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        has_one = owner @ ErrorCode::Unauthorized
     )]
     pub market_maker: Account<'info, MarketMaker>,
 
@@ -183,7 +197,14 @@ pub fn handle_deposit_liquidity(
 #[derive(Accounts)]
 pub struct WithdrawLiquidity<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, owner.key().as_ref()],
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_WITHDRAWALS) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump,
         has_one = owner @ ErrorCode::Unauthorized
     )]
@@ -269,10 +290,18 @@ pub fn handle_withdraw_liquidity(
 
 // Submit quote
 #[derive(Accounts)]
-#[instruction(asset_mint: Pubkey, quote_mint: Pubkey, strategy: StrategyType, expiry_timestamp: i64)]
+#[instruction(asset_mint: Pubkey, quote_mint: Pubkey)]
 pub struct SubmitQuote<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, owner.key().as_ref()],
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_QUOTING) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump,
         has_one = owner @ ErrorCode::Unauthorized,
         constraint = market_maker.active @ ErrorCode::MarketMakerNotActive
@@ -286,21 +315,25 @@ pub struct SubmitQuote<'info> {
         seeds = [
             QUOTE_SEED,
             market_maker.key().as_ref(),
-            asset_mint.as_ref(),
-            &[strategy as u8],
-            &expiry_timestamp.to_le_bytes()
+            &market_maker.next_quote_id.to_le_bytes()
         ],
         bump
     )]
     pub quote: Account<'info, Quote>,
 
     #[account(
+        mut,
         seeds = [ASSET_CONFIG_SEED, asset_mint.as_ref()],
         bump = asset_config.bump,
-        constraint = asset_config.enabled @ ErrorCode::AssetNotEnabled
+        constraint = asset_config.enabled @ ErrorCode::AssetNotEnabled,
+        constraint = !asset_config.is_paused(PAUSE_QUOTING) @ ErrorCode::AssetPaused,
+        constraint = asset_config.quote_mint == quote_mint @ ErrorCode::InvalidQuoteMint
     )]
     pub asset_config: Account<'info, AssetConfig>,
 
+    // Asset's Pyth price, strikes are checked against this spot
+    pub price_update: Account<'info, PriceUpdateV2>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
 
@@ -313,7 +346,8 @@ pub fn handle_submit_quote(
     quote_mint: Pubkey,
     strategy: StrategyType,
     strikes: Vec<StrikeQuote>,
-    expiry_timestamp: i64,
+    valid_until: i64,
+    option_expiry: i64,
     min_size: u64,
     max_size: u64,
 ) -> Result<()> {
@@ -322,33 +356,49 @@ pub fn handle_submit_quote(
         ErrorCode::TooManyStrikes
     );
 
-    require!(
-        min_size > 0 && max_size >= min_size,
-        ErrorCode::InvalidQuoteParameters
-    );
-
     let clock = Clock::get()?;
+    let asset_config = &ctx.accounts.asset_config;
+    asset_config.validate_expiry(option_expiry, clock.unix_timestamp)?;
     require!(
-        expiry_timestamp > clock.unix_timestamp,
-        ErrorCode::QuoteExpired
+        valid_until > clock.unix_timestamp && valid_until <= option_expiry,
+        ErrorCode::InvalidQuoteValidity
     );
 
+    let spot_price = oracle::get_price(
+        &ctx.accounts.price_update,
+        asset_config,
+        &clock,
+        ctx.accounts.global_state.max_price_age_seconds,
+    )?;
+    asset_config.validate_strikes(&strikes, spot_price)?;
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    let quote_id = market_maker.next_quote_id;
+    market_maker.next_quote_id = quote_id
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
     let quote = &mut ctx.accounts.quote;
 
-    // Initialize or update quote
-    quote.market_maker = ctx.accounts.market_maker.key();
+    quote.market_maker = market_maker.key();
+    quote.quote_id = quote_id;
     quote.asset_mint = asset_mint;
     quote.quote_mint = quote_mint;
     quote.strategy = strategy;
     quote.strikes = strikes;
-    quote.expiry_timestamp = expiry_timestamp;
+    quote.valid_until = valid_until;
+    quote.option_expiry = option_expiry;
     quote.min_size = min_size;
     quote.max_size = max_size;
     quote.last_updated = clock.unix_timestamp;
     quote.active = true;
+    quote.pending_requests = 0;
     quote.bump = ctx.bumps.quote;
+    quote.validate_sizes()?;
+
+    ctx.accounts.asset_config.record_quote_opened()?;
 
-    msg!("Quote submitted for asset: {}", asset_mint);
+    msg!("Quote {} submitted for asset: {}", quote_id, asset_mint);
 
     Ok(())
 }
@@ -357,7 +407,19 @@ pub fn handle_submit_quote(
 #[derive(Accounts)]
 pub struct UpdateQuote<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, owner.key().as_ref()],
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, quote.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump,
         has_one = owner @ ErrorCode::Unauthorized
     )]
@@ -368,38 +430,70 @@ pub struct UpdateQuote<'info> {
         seeds = [
             QUOTE_SEED,
             market_maker.key().as_ref(),
-            quote.asset_mint.as_ref(),
-            &[quote.strategy as u8]
+            &quote.quote_id.to_le_bytes()
         ],
         bump = quote.bump
     )]
     pub quote: Account<'info, Quote>,
 
+    // Asset's Pyth price, required when strikes change
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+
     pub owner: Signer<'info>,
 }
 
 pub fn handle_update_quote(
     ctx: Context<UpdateQuote>,
     strikes: Option<Vec<StrikeQuote>>,
-    expiry_timestamp: Option<i64>,
+    valid_until: Option<i64>,
     min_size: Option<u64>,
     max_size: Option<u64>,
     active: Option<bool>,
 ) -> Result<()> {
+    // Pulling a quote stays allowed while quoting is paused
+    let deactivating_only = active == Some(false)
+        && strikes.is_none()
+        && valid_until.is_none()
+        && min_size.is_none()
+        && max_size.is_none();
+    if !deactivating_only {
+        require!(
+            !ctx.accounts.global_state.is_paused(PAUSE_QUOTING),
+            ErrorCode::ProtocolPaused
+        );
+        require!(
+            !ctx.accounts.asset_config.is_paused(PAUSE_QUOTING),
+            ErrorCode::AssetPaused
+        );
+    }
+
+    let asset_config = &ctx.accounts.asset_config;
     let quote = &mut ctx.accounts.quote;
     let clock = Clock::get()?;
 
     if let Some(s) = strikes {
         require!(s.len() <= MAX_STRIKES_PER_QUOTE, ErrorCode::TooManyStrikes);
+        let price_update = ctx
+            .accounts
+            .price_update
+            .as_ref()
+            .ok_or(ErrorCode::MissingPriceUpdate)?;
+        let spot_price = oracle::get_price(
+            price_update,
+            asset_config,
+            &clock,
+            ctx.accounts.global_state.max_price_age_seconds,
+        )?;
+        asset_config.validate_strikes(&s, spot_price)?;
         quote.strikes = s;
     }
 
-    if let Some(expiry) = expiry_timestamp {
+    if let Some(until) = valid_until {
         require!(
-            expiry > clock.unix_timestamp,
-            ErrorCode::QuoteExpired
+            until > clock.unix_timestamp && until <= quote.option_expiry,
+            ErrorCode::InvalidQuoteValidity
         );
-        quote.expiry_timestamp = expiry;
+        quote.valid_until = until;
     }
 
     if let Some(min) = min_size {
@@ -414,9 +508,190 @@ pub fn handle_update_quote(
         quote.active = a;
     }
 
+    // Size bounds are checked on the resulting quote, not per field
+    quote.validate_sizes()?;
+
     quote.last_updated = clock.unix_timestamp;
 
     msg!("Quote updated");
 
     Ok(())
 }
+
+// Close a quote and reclaim its rent (MM owner)
+#[derive(Accounts)]
+pub struct CloseQuote<'info> {
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            QUOTE_SEED,
+            market_maker.key().as_ref(),
+            &quote.quote_id.to_le_bytes()
+        ],
+        bump = quote.bump,
+        constraint = quote.pending_requests == 0 @ ErrorCode::QuoteHasPendingRequests
+    )]
+    pub quote: Account<'info, Quote>,
+
+    // Releases the asset's open quote count
+    #[account(
+        mut,
+        seeds = [ASSET_CONFIG_SEED, quote.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handle_close_quote(ctx: Context<CloseQuote>) -> Result<()> {
+    ctx.accounts.asset_config.record_quote_closed()?;
+
+    msg!("Quote {} closed", ctx.accounts.quote.quote_id);
+
+    Ok(())
+}
+
+// Close a quote past its good-till time, rent goes to the MM owner (anyone)
+#[derive(Accounts)]
+pub struct CloseExpiredQuote<'info> {
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            QUOTE_SEED,
+            market_maker.key().as_ref(),
+            &quote.quote_id.to_le_bytes()
+        ],
+        bump = quote.bump,
+        constraint = quote.pending_requests == 0 @ ErrorCode::QuoteHasPendingRequests
+    )]
+    pub quote: Account<'info, Quote>,
+
+    // Releases the asset's open quote count
+    #[account(
+        mut,
+        seeds = [ASSET_CONFIG_SEED, quote.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    /// CHECK: Current MM owner, receives the quote rent
+    #[account(
+        mut,
+        address = market_maker.owner @ ErrorCode::Unauthorized
+    )]
+    pub owner: AccountInfo<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+pub fn handle_close_expired_quote(ctx: Context<CloseExpiredQuote>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts.quote.is_expired(clock.unix_timestamp),
+        ErrorCode::QuoteNotExpired
+    );
+
+    ctx.accounts.asset_config.record_quote_closed()?;
+
+    msg!("Expired quote {} closed", ctx.accounts.quote.quote_id);
+
+    Ok(())
+}
+
+// Propose a new owner (current owner)
+#[derive(Accounts)]
+pub struct ProposeMarketMakerOwner<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handle_propose_market_maker_owner(
+    ctx: Context<ProposeMarketMakerOwner>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    require!(
+        new_owner != Pubkey::default() && new_owner != ctx.accounts.owner.key(),
+        ErrorCode::InvalidPendingOwner
+    );
+
+    ctx.accounts.market_maker.pending_owner = Some(new_owner);
+
+    msg!("Market maker owner transfer proposed to: {}", new_owner);
+
+    Ok(())
+}
+
+// Accept ownership (pending owner)
+#[derive(Accounts)]
+pub struct AcceptMarketMakerOwner<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        constraint = market_maker.pending_owner == Some(new_owner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    pub new_owner: Signer<'info>,
+}
+
+pub fn handle_accept_market_maker_owner(ctx: Context<AcceptMarketMakerOwner>) -> Result<()> {
+    let market_maker = &mut ctx.accounts.market_maker;
+
+    market_maker.owner = ctx.accounts.new_owner.key();
+    market_maker.pending_owner = None;
+
+    msg!("Market maker owner transferred to: {}", market_maker.owner);
+
+    Ok(())
+}
+
+// Cancel a pending ownership transfer (current owner)
+#[derive(Accounts)]
+pub struct CancelMarketMakerOwnerTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+        constraint = market_maker.pending_owner.is_some() @ ErrorCode::NoPendingTransfer
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handle_cancel_market_maker_owner_transfer(
+    ctx: Context<CancelMarketMakerOwnerTransfer>,
+) -> Result<()> {
+    ctx.accounts.market_maker.pending_owner = None;
+
+    msg!("Market maker owner transfer cancelled");
+
+    Ok(())
+}
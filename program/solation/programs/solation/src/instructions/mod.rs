@@ -2,8 +2,14 @@ pub mod admin;
 pub mod market_maker;
 pub mod position_request;
 pub mod settlement;
+pub mod settlement_price;
+pub mod timelock;
+pub mod user_account;
 
 pub use admin::*;
 pub use market_maker::*;
 pub use position_request::*;
 pub use settlement::*;
+pub use settlement_price::*;
+pub use timelock::*;
+pub use user_account::*;
@@ -1,25 +1,26 @@
 use crate::constants::*;
 use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::math::{self, Collateral, Rounding};
 use crate::state::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 
 // ================================
 // REQUEST POSITION (User initiates)
 // ================================
 
 #[derive(Accounts)]
-#[instruction(request_id: u64, strike_price: u64)]
 pub struct RequestPosition<'info> {
     #[account(
         seeds = [GLOBAL_STATE_SEED],
         bump = global_state.bump,
-        constraint = !global_state.paused @ ErrorCode::ProtocolPaused
+        constraint = !global_state.is_paused(PAUSE_REQUESTS) @ ErrorCode::ProtocolPaused
     )]
     pub global_state: Account<'info, GlobalState>,
 
     #[account(
-        seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref()],
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump,
         constraint = market_maker.active @ ErrorCode::MarketMakerNotActive
     )]
@@ -30,9 +31,7 @@ pub struct RequestPosition<'info> {
         seeds = [
             QUOTE_SEED,
             market_maker.key().as_ref(),
-            quote.asset_mint.as_ref(),
-            &[quote.strategy as u8],
-            &quote.expiry_timestamp.to_le_bytes()
+            &quote.quote_id.to_le_bytes()
         ],
         bump = quote.bump,
         constraint = quote.active @ ErrorCode::QuoteNotActive
@@ -40,39 +39,74 @@ pub struct RequestPosition<'info> {
     pub quote: Account<'info, Quote>,
 
     #[account(
-        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
+        seeds = [ASSET_CONFIG_SEED, quote.asset_mint.as_ref()],
         bump = asset_config.bump,
-        constraint = asset_config.enabled @ ErrorCode::AssetNotEnabled
+        constraint = asset_config.enabled @ ErrorCode::AssetNotEnabled,
+        constraint = !asset_config.is_paused(PAUSE_REQUESTS) @ ErrorCode::AssetPaused,
+        constraint = asset_config.quote_mint == quote.quote_mint @ ErrorCode::InvalidQuoteMint
     )]
     pub asset_config: Account<'info, AssetConfig>,
 
+    // User's account allocating the request id
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
     #[account(
         init,
         payer = user,
         space = PositionRequest::LEN,
-        seeds = [POSITION_REQUEST_SEED, user.key().as_ref(), &request_id.to_le_bytes()],
+        seeds = [
+            POSITION_REQUEST_SEED,
+            user.key().as_ref(),
+            &user_account.next_request_id.to_le_bytes()
+        ],
         bump
     )]
     pub position_request: Account<'info, PositionRequest>,
 
+    // Escrow holding the user's collateral until the MM confirms or the request is closed
+    #[account(
+        init,
+        payer = user,
+        token::mint = collateral_mint,
+        token::authority = position_request,
+        seeds = [REQUEST_ESCROW_SEED, position_request.key().as_ref()],
+        bump
+    )]
+    pub request_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = quote.user_collateral_mint() @ ErrorCode::InvalidCollateralMint)]
+    pub collateral_mint: Account<'info, Mint>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn handle_request_position(
     ctx: Context<RequestPosition>,
-    request_id: u64,
     strike_price: u64,
     contract_size: u64,
 ) -> Result<()> {
     let clock = Clock::get()?;
     let quote = &ctx.accounts.quote;
 
-    // Validate quote not expired
+    // Validate quote still fillable
     require!(
-        clock.unix_timestamp < quote.expiry_timestamp,
+        !quote.is_expired(clock.unix_timestamp),
         ErrorCode::QuoteExpired
     );
 
@@ -100,10 +134,29 @@ pub fn handle_request_position(
     );
 
     // Calculate premium
-    let premium = strike_quote
-        .premium_per_contract
-        .checked_mul(contract_size)
-        .ok_or(ErrorCode::MathOverflow)?;
+    let premium = math::premium_amount(
+        strike_quote.premium_per_contract,
+        contract_size,
+        ctx.accounts.asset_config.decimals,
+    )?;
+
+    let collateral = math::position_collateral(
+        quote.strategy,
+        strike_price,
+        contract_size,
+        ctx.accounts.asset_config.decimals,
+    )?;
+
+    // Move the user's collateral into the request escrow while they are signing
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_token_account.to_account_info(),
+        to: ctx.accounts.request_escrow.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), collateral.user)?;
+
+    let request_id = ctx.accounts.user_account.allocate_request_id()?;
 
     // Initialize position request
     let request = &mut ctx.accounts.position_request;
@@ -117,10 +170,13 @@ pub fn handle_request_position(
     request.strike_price = strike_price;
     request.contract_size = contract_size;
     request.premium = premium;
+    request.escrow_amount = collateral.user;
     request.created_at = clock.unix_timestamp;
     request.expires_at = clock.unix_timestamp + MM_CONFIRMATION_WINDOW;
-    request.status = RequestStatus::Pending;
     request.bump = ctx.bumps.position_request;
+    request.escrow_bump = ctx.bumps.request_escrow;
+
+    ctx.accounts.quote.open_request()?;
 
     msg!(
         "Position request created: {} (expires at {})",
@@ -136,61 +192,83 @@ pub fn handle_request_position(
 // ================================
 
 #[derive(Accounts)]
-#[instruction(position_id: u64)]
 pub struct ConfirmPosition<'info> {
     #[account(
         mut,
         seeds = [GLOBAL_STATE_SEED],
         bump = global_state.bump,
-        constraint = !global_state.paused @ ErrorCode::ProtocolPaused
+        constraint = !global_state.is_paused(PAUSE_CONFIRMS) @ ErrorCode::ProtocolPaused
     )]
-    pub global_state: Account<'info, GlobalState>,
+    pub global_state: Box<Account<'info, GlobalState>>,
 
     #[account(
         mut,
-        seeds = [MARKET_MAKER_SEED, mm_owner.key().as_ref()],
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump,
+        constraint = market_maker.owner == mm_owner.key() @ ErrorCode::Unauthorized,
         constraint = market_maker.active @ ErrorCode::MarketMakerNotActive
     )]
-    pub market_maker: Account<'info, MarketMaker>,
+    pub market_maker: Box<Account<'info, MarketMaker>>,
 
+    // Closed to the user once the position is opened
     #[account(
         mut,
-        constraint = position_request.market_maker == market_maker.key() @ ErrorCode::UnauthorizedConfirmation,
-        constraint = position_request.is_pending() @ ErrorCode::RequestNotPending
+        close = user,
+        seeds = [
+            POSITION_REQUEST_SEED,
+            position_request.user.as_ref(),
+            &position_request.request_id.to_le_bytes()
+        ],
+        bump = position_request.bump,
+        has_one = market_maker @ ErrorCode::UnauthorizedConfirmation,
+        has_one = quote @ ErrorCode::QuoteMismatch,
+        has_one = user @ ErrorCode::Unauthorized
     )]
-    pub position_request: Account<'info, PositionRequest>,
+    pub position_request: Box<Account<'info, PositionRequest>>,
 
     #[account(
         mut,
         seeds = [
             QUOTE_SEED,
             market_maker.key().as_ref(),
-            quote.asset_mint.as_ref(),
-            &[quote.strategy as u8],
-            &quote.expiry_timestamp.to_le_bytes()
+            &quote.quote_id.to_le_bytes()
         ],
         bump = quote.bump
     )]
-    pub quote: Account<'info, Quote>,
+    pub quote: Box<Account<'info, Quote>>,
 
     #[account(
-        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
-        bump = asset_config.bump
+        mut,
+        seeds = [ASSET_CONFIG_SEED, position_request.asset_mint.as_ref()],
+        bump = asset_config.bump,
+        constraint = !asset_config.is_paused(PAUSE_CONFIRMS) @ ErrorCode::AssetPaused,
+        constraint = asset_config.quote_mint == quote.quote_mint @ ErrorCode::InvalidQuoteMint
     )]
-    pub asset_config: Account<'info, AssetConfig>,
+    pub asset_config: Box<Account<'info, AssetConfig>>,
+
+    // User's account allocating the position id
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, position_request.user.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
 
     // Position account
     #[account(
         init,
         payer = mm_owner,
         space = Position::LEN,
-        seeds = [POSITION_SEED, position_request.user.as_ref(), &position_id.to_le_bytes()],
+        seeds = [
+            POSITION_SEED,
+            position_request.user.as_ref(),
+            &user_account.next_position_id.to_le_bytes()
+        ],
         bump
     )]
-    pub position: Account<'info, Position>,
+    pub position: Box<Account<'info, Position>>,
 
-    // User's vault (holds user's locked asset)
+    // User's vault (holds user's locked collateral)
     #[account(
         init,
         payer = mm_owner,
@@ -199,9 +277,9 @@ pub struct ConfirmPosition<'info> {
         seeds = [POSITION_USER_VAULT_SEED, position.key().as_ref()],
         bump
     )]
-    pub position_user_vault: Account<'info, TokenAccount>,
+    pub position_user_vault: Box<Account<'info, TokenAccount>>,
 
-    // MM's vault (holds MM's locked asset)
+    // MM's vault (holds MM's locked collateral)
     #[account(
         init,
         payer = mm_owner,
@@ -210,28 +288,37 @@ pub struct ConfirmPosition<'info> {
         seeds = [POSITION_MM_VAULT_SEED, position.key().as_ref()],
         bump
     )]
-    pub position_mm_vault: Account<'info, TokenAccount>,
+    pub position_mm_vault: Box<Account<'info, TokenAccount>>,
 
     /// CHECK: PDA authority for position vaults
     #[account(
-        seeds = [POSITION_SEED, position_request.user.as_ref(), &position_id.to_le_bytes()],
+        seeds = [
+            POSITION_SEED,
+            position_request.user.as_ref(),
+            &user_account.next_position_id.to_le_bytes()
+        ],
         bump
     )]
     pub position_vault_authority: AccountInfo<'info>,
 
-    // Market maker's main vault account
+    // Market maker's collateral vault for this strategy
     #[account(
         mut,
-        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), mm_vault.asset_mint.as_ref()],
-        bump = mm_vault.bump
+        seeds = [
+            MM_VAULT_SEED,
+            market_maker.key().as_ref(),
+            position_request.mm_collateral_mint().as_ref()
+        ],
+        bump = mm_vault.bump,
+        has_one = market_maker @ ErrorCode::Unauthorized
     )]
-    pub mm_vault: Account<'info, MarketMakerVault>,
+    pub mm_vault: Box<Account<'info, MarketMakerVault>>,
 
     #[account(
         mut,
-        token::mint = mm_asset_mint
+        address = mm_vault.vault_token_account @ ErrorCode::InvalidVaultTokenAccount
     )]
-    pub mm_vault_token_account: Account<'info, TokenAccount>,
+    pub mm_vault_token_account: Box<Account<'info, TokenAccount>>,
 
     /// CHECK: PDA authority for MM vault
     #[account(
@@ -240,48 +327,58 @@ pub struct ConfirmPosition<'info> {
     )]
     pub mm_vault_authority: AccountInfo<'info>,
 
-    // MM's premium vault (USDC for premium payment)
+    // MM's quote mint vault paying the premium, cash secured puts only. Covered
+    // calls pay it from mm_vault, which already holds the quote mint.
     #[account(
         mut,
-        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), premium_mint.key().as_ref()],
-        bump = mm_premium_vault.bump
+        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), position_request.quote_mint.as_ref()],
+        bump = mm_premium_vault.bump,
+        has_one = market_maker @ ErrorCode::Unauthorized
     )]
-    pub mm_premium_vault: Account<'info, MarketMakerVault>,
+    pub mm_premium_vault: Option<Box<Account<'info, MarketMakerVault>>>,
 
     #[account(
         mut,
-        token::mint = premium_mint
+        constraint = mm_premium_vault.as_ref().map(|v| v.vault_token_account)
+            == Some(mm_premium_vault_token_account.key()) @ ErrorCode::InvalidVaultTokenAccount
     )]
-    pub mm_premium_vault_token_account: Account<'info, TokenAccount>,
+    pub mm_premium_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
-    /// CHECK: PDA authority for MM premium vault
+    // Request escrow holding the user's collateral
     #[account(
-        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), premium_mint.key().as_ref()],
-        bump = mm_premium_vault.bump
+        mut,
+        token::mint = user_asset_mint,
+        seeds = [REQUEST_ESCROW_SEED, position_request.key().as_ref()],
+        bump = position_request.escrow_bump
     )]
-    pub mm_premium_vault_authority: AccountInfo<'info>,
+    pub request_escrow: Box<Account<'info, TokenAccount>>,
 
-    // User's token accounts
+    // Treasury's account for the premium mint (receives the protocol fee)
     #[account(
         mut,
-        token::mint = user_asset_mint,
-        token::authority = user
+        token::mint = premium_mint,
+        token::authority = global_state.treasury
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
 
+    // User's premium account
     #[account(
         mut,
         token::mint = premium_mint,
         token::authority = user
     )]
-    pub user_premium_account: Account<'info, TokenAccount>,
+    pub user_premium_account: Box<Account<'info, TokenAccount>>,
 
-    // Mints
-    pub user_asset_mint: Account<'info, Mint>,
-    pub mm_asset_mint: Account<'info, Mint>,
-    pub premium_mint: Account<'info, Mint>,
+    // Mints, derived from the request's strategy rather than trusted
+    #[account(address = position_request.user_collateral_mint() @ ErrorCode::InvalidCollateralMint)]
+    pub user_asset_mint: Box<Account<'info, Mint>>,
+    #[account(address = position_request.mm_collateral_mint() @ ErrorCode::InvalidCollateralMint)]
+    pub mm_asset_mint: Box<Account<'info, Mint>>,
+    #[account(address = position_request.quote_mint @ ErrorCode::InvalidQuoteMint)]
+    pub premium_mint: Box<Account<'info, Mint>>,
 
-    /// CHECK: User who made the request
+    /// CHECK: User who made the request (receives escrow and request rent refunds), bound by has_one
+    #[account(mut)]
     pub user: AccountInfo<'info>,
 
     #[account(mut)]
@@ -291,7 +388,7 @@ pub struct ConfirmPosition<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_confirm_position(ctx: Context<ConfirmPosition>, position_id: u64) -> Result<()> {
+pub fn handle_confirm_position(ctx: Context<ConfirmPosition>) -> Result<()> {
     let clock = Clock::get()?;
     let request = &ctx.accounts.position_request;
 
@@ -307,40 +404,100 @@ pub fn handle_confirm_position(ctx: Context<ConfirmPosition>, position_id: u64)
     let premium = request.premium;
     let strategy = request.strategy;
 
-    // Execute based on strategy
+    let collateral = math::position_collateral(
+        strategy,
+        strike_price,
+        contract_size,
+        ctx.accounts.asset_config.decimals,
+    )?;
+
+    // Protocol fee is skimmed from the premium, capped by the hard maximum
+    let fee_bps = ctx
+        .accounts
+        .global_state
+        .protocol_fee_bps
+        .min(MAX_PROTOCOL_FEE_BPS);
+    let protocol_fee = math::mul_div(
+        premium,
+        fee_bps as u64,
+        BASIS_POINTS_DIVISOR as u128,
+        Rounding::Down,
+    )?;
+    let user_premium = premium
+        .checked_sub(protocol_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Consume quote capacity; fails if a concurrent fill already took it
+    ctx.accounts
+        .quote
+        .fill_strike(strike_price, contract_size)?;
+    ctx.accounts.quote.close_request()?;
+
+    // Reserve MM collateral and the premium about to be paid out
+    ctx.accounts.mm_vault.lock(collateral.mm)?;
+    match (strategy, ctx.accounts.mm_premium_vault.as_mut()) {
+        (StrategyType::CoveredCall, None) => ctx.accounts.mm_vault.debit(premium)?,
+        (StrategyType::CashSecuredPut, Some(premium_vault)) => premium_vault.debit(premium)?,
+        _ => return err!(ErrorCode::InvalidPremiumVault),
+    }
+
+    // Move the user's escrowed collateral into the position vault
+    release_escrow(
+        &ctx.accounts.position_request,
+        &ctx.accounts.request_escrow,
+        ctx.accounts.position_user_vault.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    // Lock MM collateral and pay out the premium and protocol fee
+    execute_payout(&ctx, collateral, user_premium, protocol_fee)?;
     match strategy {
-        StrategyType::CoveredCall => {
-            execute_covered_call(&ctx, position_id, strike_price, contract_size, premium)?;
-        }
+        StrategyType::CoveredCall => msg!("Covered call executed - collateral locked, premium paid"),
         StrategyType::CashSecuredPut => {
-            execute_cash_secured_put(&ctx, position_id, strike_price, contract_size, premium)?;
+            msg!("Cash secured put executed - collateral locked, premium paid")
         }
     }
 
+    let position_id = ctx.accounts.user_account.allocate_position_id()?;
+    let notional = math::quote_value(
+        strike_price,
+        contract_size,
+        ctx.accounts.asset_config.decimals,
+        Rounding::Down,
+    )?;
+    ctx.accounts
+        .user_account
+        .record_position_opened(user_premium, notional)?;
+    ctx.accounts.asset_config.record_position_opened()?;
+
     // Initialize position
     let position = &mut ctx.accounts.position;
     position.position_id = position_id;
+    position.request = ctx.accounts.position_request.key();
+    position.request_id = ctx.accounts.position_request.request_id;
     position.user = ctx.accounts.position_request.user;
     position.market_maker = ctx.accounts.market_maker.key();
     position.strategy = strategy;
     position.asset_mint = ctx.accounts.asset_config.asset_mint;
     position.quote_mint = ctx.accounts.quote.quote_mint;
     position.strike_price = strike_price;
-    position.premium_paid = premium;
+    position.premium_paid = user_premium;
+    position.protocol_fee = protocol_fee;
     position.contract_size = contract_size;
+    position.user_collateral = collateral.user;
+    position.mm_collateral = collateral.mm;
     position.created_at = clock.unix_timestamp;
-    position.expiry_timestamp = ctx.accounts.quote.expiry_timestamp;
+    position.expiry_timestamp = ctx.accounts.quote.option_expiry;
     position.settlement_price = None;
     position.status = PositionStatus::Active;
     position.user_vault = ctx.accounts.position_user_vault.key();
     position.mm_vault_locked = ctx.accounts.position_mm_vault.key();
+    position.rent_payer = ctx.accounts.mm_owner.key();
     position.bump = ctx.bumps.position;
     position.user_vault_bump = ctx.bumps.position_user_vault;
     position.mm_vault_bump = ctx.bumps.position_mm_vault;
 
-    // Update request status
-    ctx.accounts.position_request.status = RequestStatus::Accepted;
-
     // Update global state
     ctx.accounts.global_state.total_positions = ctx
         .accounts
@@ -354,6 +511,12 @@ pub fn handle_confirm_position(ctx: Context<ConfirmPosition>, position_id: u64)
         .total_volume
         .checked_add(contract_size)
         .ok_or(ErrorCode::MathOverflow)?;
+    ctx.accounts.global_state.total_fees_collected = ctx
+        .accounts
+        .global_state
+        .total_fees_collected
+        .checked_add(protocol_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
 
     // Update market maker stats
     ctx.accounts.market_maker.total_positions = ctx
@@ -363,55 +526,36 @@ pub fn handle_confirm_position(ctx: Context<ConfirmPosition>, position_id: u64)
         .checked_add(1)
         .ok_or(ErrorCode::MathOverflow)?;
 
+    emit!(PositionConfirmed {
+        position: position.key(),
+        request_id: position.request_id,
+        user: position.user,
+        market_maker: position.market_maker,
+        premium: user_premium,
+        protocol_fee,
+        premium_mint: position.quote_mint,
+    });
+
     msg!("Position confirmed: {}", position.key());
 
     Ok(())
 }
 
-fn execute_covered_call(
+fn execute_payout(
     ctx: &Context<ConfirmPosition>,
-    _position_id: u64,
-    strike_price: u64,
-    contract_size: u64,
-    premium: u64,
+    collateral: Collateral,
+    user_premium: u64,
+    protocol_fee: u64,
 ) -> Result<()> {
-    // Covered Call:
-    // - User deposits underlying asset (contract_size)
-    // - MM locks USDC (strike_price * contract_size)
-    // - MM pays premium to user immediately
-
-    let strike_amount = strike_price
-        .checked_mul(contract_size)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(10u64.pow(ctx.accounts.asset_config.decimals as u32))
-        .ok_or(ErrorCode::MathOverflow)?;
-
-    // Check MM has enough USDC liquidity
-    require!(
-        ctx.accounts.mm_vault.available_liquidity >= strike_amount,
-        ErrorCode::InsufficientLiquidity
-    );
-
-    // Check MM has enough premium USDC
-    require!(
-        ctx.accounts.mm_premium_vault.available_liquidity >= premium,
-        ErrorCode::InsufficientLiquidity
-    );
+    // Both strategies settle the same way once the user's collateral has
+    // moved from escrow (see math::position_collateral for the amounts):
+    // - Covered call: MM locks the strike notional in the quote mint
+    // - Cash secured put: MM locks the underlying (contract_size)
+    // and the MM then pays the premium to the user, minus the protocol fee
 
     let cpi_program = ctx.accounts.token_program.to_account_info();
 
-    // 1. Transfer user's underlying asset to position_user_vault
-    let cpi_accounts_user = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.position_user_vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new(cpi_program.clone(), cpi_accounts_user),
-        contract_size,
-    )?;
-
-    // 2. Transfer MM's USDC to position_mm_vault
+    // 1. Transfer MM's collateral to position_mm_vault
     let market_maker_key = ctx.accounts.market_maker.key();
     let asset_mint_key = ctx.accounts.mm_vault.asset_mint;
     let mm_vault_bump = ctx.accounts.mm_vault.bump;
@@ -430,163 +574,201 @@ fn execute_covered_call(
     };
     token::transfer(
         CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts_mm, mm_vault_signer),
-        strike_amount,
+        collateral.mm,
     )?;
 
-    // 3. Transfer premium from MM to user
-    let premium_mint_key = ctx.accounts.mm_premium_vault.asset_mint;
-    let mm_premium_bump = ctx.accounts.mm_premium_vault.bump;
+    // 2. Transfer premium (net of protocol fee) from MM to user
+    let (premium_vault, premium_token_account) = premium_source(ctx)?;
+    let premium_mint_key = premium_vault.asset_mint;
     let mm_premium_seeds = &[
         MM_VAULT_SEED,
         market_maker_key.as_ref(),
         premium_mint_key.as_ref(),
-        &[mm_premium_bump],
+        &[premium_vault.bump],
     ];
     let mm_premium_signer = &[&mm_premium_seeds[..]];
 
+    // The vault PDA is its token account's authority
     let cpi_accounts_premium = Transfer {
-        from: ctx
-            .accounts
-            .mm_premium_vault_token_account
-            .to_account_info(),
+        from: premium_token_account.to_account_info(),
         to: ctx.accounts.user_premium_account.to_account_info(),
-        authority: ctx.accounts.mm_premium_vault_authority.to_account_info(),
+        authority: premium_vault.to_account_info(),
     };
     token::transfer(
-        CpiContext::new_with_signer(cpi_program, cpi_accounts_premium, mm_premium_signer),
-        premium,
+        CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts_premium, mm_premium_signer),
+        user_premium,
     )?;
 
-    msg!("Covered call executed - collateral locked, premium paid");
+    // 3. Transfer protocol fee from MM to treasury
+    if protocol_fee > 0 {
+        let cpi_accounts_fee = Transfer {
+            from: premium_token_account.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: premium_vault.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts_fee, mm_premium_signer),
+            protocol_fee,
+        )?;
+    }
 
     Ok(())
 }
 
-fn execute_cash_secured_put(
-    ctx: &Context<ConfirmPosition>,
-    _position_id: u64,
-    strike_price: u64,
-    contract_size: u64,
-    premium: u64,
-) -> Result<()> {
-    // Cash Secured Put:
-    // - MM deposits underlying asset (contract_size)
-    // - User deposits USDC (strike_price * contract_size)
-    // - MM pays premium to user immediately
-
-    let strike_amount = strike_price
-        .checked_mul(contract_size)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(10u64.pow(ctx.accounts.asset_config.decimals as u32))
-        .ok_or(ErrorCode::MathOverflow)?;
+/// MM vault and token account the premium is paid from: the collateral vault
+/// for covered calls, the separate quote mint vault for cash secured puts
+fn premium_source<'a, 'info>(
+    ctx: &'a Context<ConfirmPosition<'info>>,
+) -> Result<(&'a Account<'info, MarketMakerVault>, &'a Account<'info, TokenAccount>)> {
+    match (
+        &ctx.accounts.mm_premium_vault,
+        &ctx.accounts.mm_premium_vault_token_account,
+    ) {
+        (None, None) => Ok((&ctx.accounts.mm_vault, &ctx.accounts.mm_vault_token_account)),
+        (Some(vault), Some(token_account)) => Ok((vault, token_account)),
+        _ => err!(ErrorCode::InvalidPremiumVault),
+    }
+}
 
-    // Check MM has enough underlying asset
-    require!(
-        ctx.accounts.mm_vault.available_liquidity >= contract_size,
-        ErrorCode::InsufficientLiquidity
-    );
+// ================================
+// REJECT REQUEST (MM rejects)
+// ================================
 
-    // Check MM has enough premium USDC
-    require!(
-        ctx.accounts.mm_premium_vault.available_liquidity >= premium,
-        ErrorCode::InsufficientLiquidity
-    );
+#[derive(Accounts)]
+pub struct RejectRequest<'info> {
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump,
+        constraint = market_maker.owner == mm_owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
 
-    let cpi_program = ctx.accounts.token_program.to_account_info();
+    #[account(
+        mut,
+        close = user,
+        constraint = position_request.market_maker == market_maker.key() @ ErrorCode::UnauthorizedConfirmation
+    )]
+    pub position_request: Account<'info, PositionRequest>,
 
-    // 1. Transfer user's USDC to position_user_vault
-    let cpi_accounts_user = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.position_user_vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new(cpi_program.clone(), cpi_accounts_user),
-        strike_amount,
-    )?;
+    #[account(
+        mut,
+        address = position_request.quote
+    )]
+    pub quote: Account<'info, Quote>,
 
-    // 2. Transfer MM's underlying asset to position_mm_vault
-    let market_maker_key = ctx.accounts.market_maker.key();
-    let asset_mint_key = ctx.accounts.mm_vault.asset_mint;
-    let mm_vault_bump = ctx.accounts.mm_vault.bump;
-    let mm_vault_seeds = &[
-        MM_VAULT_SEED,
-        market_maker_key.as_ref(),
-        asset_mint_key.as_ref(),
-        &[mm_vault_bump],
-    ];
-    let mm_vault_signer = &[&mm_vault_seeds[..]];
+    #[account(
+        mut,
+        seeds = [REQUEST_ESCROW_SEED, position_request.key().as_ref()],
+        bump = position_request.escrow_bump
+    )]
+    pub request_escrow: Account<'info, TokenAccount>,
 
-    let cpi_accounts_mm = Transfer {
-        from: ctx.accounts.mm_vault_token_account.to_account_info(),
-        to: ctx.accounts.position_mm_vault.to_account_info(),
-        authority: ctx.accounts.mm_vault_authority.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts_mm, mm_vault_signer),
-        contract_size,
-    )?;
+    // User's account receiving the escrow refund
+    #[account(
+        mut,
+        token::mint = request_escrow.mint,
+        token::authority = position_request.user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
 
-    // 3. Transfer premium from MM to user
-    let premium_mint_key = ctx.accounts.mm_premium_vault.asset_mint;
-    let mm_premium_bump = ctx.accounts.mm_premium_vault.bump;
-    let mm_premium_seeds = &[
-        MM_VAULT_SEED,
-        market_maker_key.as_ref(),
-        premium_mint_key.as_ref(),
-        &[mm_premium_bump],
-    ];
-    let mm_premium_signer = &[&mm_premium_seeds[..]];
+    /// CHECK: User who made the request (receives rent refund)
+    #[account(
+        mut,
+        constraint = user.key() == position_request.user
+    )]
+    pub user: AccountInfo<'info>,
 
-    let cpi_accounts_premium = Transfer {
-        from: ctx
-            .accounts
-            .mm_premium_vault_token_account
-            .to_account_info(),
-        to: ctx.accounts.user_premium_account.to_account_info(),
-        authority: ctx.accounts.mm_premium_vault_authority.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new_with_signer(cpi_program, cpi_accounts_premium, mm_premium_signer),
-        premium,
-    )?;
+    pub mm_owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
 
-    msg!("Cash secured put executed - collateral locked, premium paid");
+pub fn handle_reject_request(ctx: Context<RejectRequest>) -> Result<()> {
+    ctx.accounts.quote.close_request()?;
+
+    // Refund the escrowed collateral before the request is closed
+    release_escrow(
+        &ctx.accounts.position_request,
+        &ctx.accounts.request_escrow,
+        ctx.accounts.user_token_account.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
 
+    // The request is closed and rent returned to user
+    // Status is implicitly "Rejected" since account is closed
+    msg!("Position request rejected by MM");
     Ok(())
 }
 
 // ================================
-// REJECT REQUEST (MM rejects)
+// CANCEL REQUEST (User withdraws)
 // ================================
 
 #[derive(Accounts)]
-pub struct RejectRequest<'info> {
+pub struct CancelRequest<'info> {
     #[account(
-        seeds = [MARKET_MAKER_SEED, mm_owner.key().as_ref()],
-        bump = market_maker.bump
+        mut,
+        close = user,
+        seeds = [
+            POSITION_REQUEST_SEED,
+            position_request.user.as_ref(),
+            &position_request.request_id.to_le_bytes()
+        ],
+        bump = position_request.bump,
+        has_one = user @ ErrorCode::Unauthorized
     )]
-    pub market_maker: Account<'info, MarketMaker>,
+    pub position_request: Account<'info, PositionRequest>,
 
     #[account(
         mut,
-        close = user,
-        constraint = position_request.market_maker == market_maker.key() @ ErrorCode::UnauthorizedConfirmation,
-        constraint = position_request.is_pending() @ ErrorCode::RequestNotPending
+        address = position_request.quote
     )]
-    pub position_request: Account<'info, PositionRequest>,
+    pub quote: Account<'info, Quote>,
+
+    #[account(
+        mut,
+        seeds = [REQUEST_ESCROW_SEED, position_request.key().as_ref()],
+        bump = position_request.escrow_bump
+    )]
+    pub request_escrow: Account<'info, TokenAccount>,
+
+    // User's account receiving the escrow refund
+    #[account(
+        mut,
+        token::mint = request_escrow.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
 
-    /// CHECK: User who made the request (receives rent refund)
     #[account(mut)]
-    pub user: AccountInfo<'info>,
+    pub user: Signer<'info>,
 
-    pub mm_owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-pub fn handle_reject_request(_ctx: Context<RejectRequest>) -> Result<()> {
-    // The request is closed and rent returned to user
-    // Status is implicitly "Rejected" since account is closed
-    msg!("Position request rejected by MM");
+pub fn handle_cancel_request(ctx: Context<CancelRequest>) -> Result<()> {
+    ctx.accounts.quote.close_request()?;
+
+    // Refund the escrowed collateral before the request is closed
+    release_escrow(
+        &ctx.accounts.position_request,
+        &ctx.accounts.request_escrow,
+        ctx.accounts.user_token_account.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    // Lets the MM's confirmation bot drop the request
+    emit!(RequestCancelled {
+        request: ctx.accounts.position_request.key(),
+        request_id: ctx.accounts.position_request.request_id,
+        user: ctx.accounts.position_request.user,
+        market_maker: ctx.accounts.position_request.market_maker,
+        quote: ctx.accounts.position_request.quote,
+    });
+
+    msg!("Position request cancelled by user");
     Ok(())
 }
 
@@ -598,11 +780,31 @@ pub fn handle_reject_request(_ctx: Context<RejectRequest>) -> Result<()> {
 pub struct CancelExpiredRequest<'info> {
     #[account(
         mut,
-        close = user,
-        constraint = position_request.is_pending() @ ErrorCode::RequestNotPending
+        close = user
     )]
     pub position_request: Account<'info, PositionRequest>,
 
+    #[account(
+        mut,
+        address = position_request.quote
+    )]
+    pub quote: Account<'info, Quote>,
+
+    #[account(
+        mut,
+        seeds = [REQUEST_ESCROW_SEED, position_request.key().as_ref()],
+        bump = position_request.escrow_bump
+    )]
+    pub request_escrow: Account<'info, TokenAccount>,
+
+    // User's account receiving the escrow refund
+    #[account(
+        mut,
+        token::mint = request_escrow.mint,
+        token::authority = position_request.user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
     /// CHECK: User who made the request (receives rent refund)
     #[account(
         mut,
@@ -612,6 +814,8 @@ pub struct CancelExpiredRequest<'info> {
 
     /// Anyone can call this after expiry
     pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 pub fn handle_cancel_expired_request(ctx: Context<CancelExpiredRequest>) -> Result<()> {
@@ -625,7 +829,62 @@ pub fn handle_cancel_expired_request(ctx: Context<CancelExpiredRequest>) -> Resu
         ErrorCode::RequestNotExpired
     );
 
+    ctx.accounts.quote.close_request()?;
+
+    // Refund the escrowed collateral before the request is closed
+    release_escrow(
+        &ctx.accounts.position_request,
+        &ctx.accounts.request_escrow,
+        ctx.accounts.user_token_account.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
     // The request is closed and rent returned to user
     msg!("Expired position request cancelled");
     Ok(())
 }
+
+// ================================
+// ESCROW HELPERS
+// ================================
+
+/// Moves the full escrow balance to `destination` and closes the escrow,
+/// returning its rent to `rent_receiver`. Signed by the request PDA.
+fn release_escrow<'info>(
+    request: &Account<'info, PositionRequest>,
+    escrow: &Account<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+    rent_receiver: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let request_id = request.request_id.to_le_bytes();
+    let request_seeds = &[
+        POSITION_REQUEST_SEED,
+        request.user.as_ref(),
+        request_id.as_ref(),
+        &[request.bump],
+    ];
+    let request_signer = &[&request_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: escrow.to_account_info(),
+        to: destination,
+        authority: request.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.clone(), cpi_accounts, request_signer),
+        escrow.amount,
+    )?;
+
+    let cpi_accounts_close = CloseAccount {
+        account: escrow.to_account_info(),
+        destination: rent_receiver,
+        authority: request.to_account_info(),
+    };
+    token::close_account(CpiContext::new_with_signer(
+        token_program,
+        cpi_accounts_close,
+        request_signer,
+    ))
+}
@@ -1,28 +1,40 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
 use crate::state::*;
 use crate::constants::*;
 use crate::errors::ErrorCode;
+use crate::events::*;
 
 // Settle position
 #[derive(Accounts)]
 pub struct SettlePosition<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
     #[account(
         mut,
+        seeds = [POSITION_SEED, position.user.as_ref(), &position.position_id.to_le_bytes()],
+        bump = position.bump,
+        has_one = market_maker @ ErrorCode::Unauthorized,
         constraint = position.status == PositionStatus::Active @ ErrorCode::PositionNotActive
     )]
     pub position: Account<'info, Position>,
 
     #[account(
-        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
-        bump = asset_config.bump
+        mut,
+        seeds = [ASSET_CONFIG_SEED, position.asset_mint.as_ref()],
+        bump = asset_config.bump,
+        constraint = !asset_config.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::AssetPaused
     )]
     pub asset_config: Account<'info, AssetConfig>,
 
     #[account(
         mut,
-        seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref()],
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
         bump = market_maker.bump
     )]
     pub market_maker: Account<'info, MarketMaker>,
@@ -30,37 +42,79 @@ pub struct SettlePosition<'info> {
     // Position vaults
     #[account(
         mut,
-        token::authority = position_vault_authority
+        address = position.user_vault @ ErrorCode::InvalidPositionVault
     )]
     pub position_user_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        token::authority = position_vault_authority
+        address = position.mm_vault_locked @ ErrorCode::InvalidPositionVault
     )]
     pub position_mm_vault: Account<'info, TokenAccount>,
 
-    /// CHECK: PDA authority for position vaults (derived with position_id)
+    /// CHECK: PDA authority for position vaults (the position account itself)
+    #[account(
+        seeds = [POSITION_SEED, position.user.as_ref(), &position.position_id.to_le_bytes()],
+        bump = position.bump
+    )]
     pub position_vault_authority: AccountInfo<'info>,
 
-    // Market maker's vault (to unlock liquidity)
+    // Market maker's collateral vault (to unlock liquidity)
     #[account(
         mut,
-        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), mm_vault.asset_mint.as_ref()],
-        bump = mm_vault.bump
+        seeds = [MM_VAULT_SEED, market_maker.key().as_ref(), position.mm_collateral_mint().as_ref()],
+        bump = mm_vault.bump,
+        has_one = market_maker @ ErrorCode::Unauthorized
     )]
     pub mm_vault: Account<'info, MarketMakerVault>,
 
-    // Destination accounts for settlement
-    #[account(mut)]
-    pub user_destination: Account<'info, TokenAccount>,
+    // Position owner's account (open position count)
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, position.user.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
 
-    #[account(mut)]
-    pub mm_destination: Account<'info, TokenAccount>,
+    // Destination accounts for settlement, owned by the position's user and MM
+    #[account(
+        mut,
+        token::mint = position.asset_mint,
+        token::authority = position.user
+    )]
+    pub user_asset_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = position.quote_mint,
+        token::authority = position.user
+    )]
+    pub user_quote_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = position.asset_mint,
+        token::authority = market_maker.owner
+    )]
+    pub mm_asset_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = position.quote_mint,
+        token::authority = market_maker.owner
+    )]
+    pub mm_quote_account: Account<'info, TokenAccount>,
 
-    // Pyth price feed
-    /// CHECK: Validated by Pyth SDK
-    pub price_update: AccountInfo<'info>,
+    // Expiry price shared by all positions on this asset and expiry
+    #[account(
+        seeds = [
+            SETTLEMENT_PRICE_SEED,
+            position.asset_mint.as_ref(),
+            &position.expiry_timestamp.to_le_bytes()
+        ],
+        bump = settlement_price_record.bump
+    )]
+    pub settlement_price_record: Account<'info, SettlementPriceRecord>,
 
     pub token_program: Program<'info, Token>,
 }
@@ -74,70 +128,43 @@ pub fn handle_settle_position(mut ctx: Context<SettlePosition>) -> Result<()> {
         ErrorCode::PositionNotExpired
     );
 
-    // Load Pyth price update and extract settlement price
-    let settlement_price = {
-        let price_update_account = &ctx.accounts.price_update;
-        let price_update_data = price_update_account.try_borrow_data()
-            .map_err(|_| ErrorCode::PriceTooStale)?;
-
-        let price_update = PriceUpdateV2::try_from_slice(&price_update_data)
-            .map_err(|_| ErrorCode::PriceTooStale)?;
-
-        // Get price with staleness check
-        // Note: Pyth SDK v1.x uses get_price_unchecked
-        let price = price_update.get_price_unchecked(&ctx.accounts.asset_config.pyth_feed_id)
-            .map_err(|_| ErrorCode::PythFeedIdMismatch)?;
-
-        // Manual staleness check
-        let price_timestamp = price_update.price_message.publish_time;
-        require!(
-            clock.unix_timestamp - price_timestamp < PYTH_STALENESS_THRESHOLD as i64,
-            ErrorCode::PriceTooStale
-        );
-
-        // Verify feed ID matches
-        require!(
-            price_update.price_message.feed_id == ctx.accounts.asset_config.pyth_feed_id,
-            ErrorCode::PythFeedIdMismatch
-        );
-
-        // Convert price to u64 (handle negative prices by taking absolute value)
-        price.price.abs() as u64
-    };
+    let settlement_price = ctx
+        .accounts
+        .settlement_price_record
+        .settlement_price
+        .ok_or(ErrorCode::SettlementPriceNotPublished)?;
 
     msg!("Settlement price: {}", settlement_price);
     msg!("Strike price: {}", ctx.accounts.position.strike_price);
 
-    // Store settlement price and strategy before borrowing ctx mutably
     ctx.accounts.position.settlement_price = Some(settlement_price);
-    let strategy = ctx.accounts.position.strategy;
     let position_key = ctx.accounts.position.key();
 
-    // Settle based on strategy
-    match strategy {
-        StrategyType::CoveredCall => {
-            settle_covered_call(&mut ctx, settlement_price)?;
-        }
-        StrategyType::CashSecuredPut => {
-            settle_cash_secured_put(&mut ctx, settlement_price)?;
-        }
-    }
+    settle_collateral(&mut ctx, settlement_price)?;
+
+    ctx.accounts.user_account.record_position_settled()?;
+    ctx.accounts.asset_config.record_position_settled()?;
 
     msg!("Position settled: {}", position_key);
 
     Ok(())
 }
 
-fn settle_covered_call(
-    ctx: &mut Context<SettlePosition>,
-    settlement_price: u64,
-) -> Result<()> {
+/// Pays out both position vaults by the strategy's collateral mints: in the
+/// money the two sides swap collateral, otherwise each side gets its own back
+fn settle_collateral(ctx: &mut Context<SettlePosition>, settlement_price: u64) -> Result<()> {
     // Extract position data before any mutable borrows
     let position_bump = ctx.accounts.position.bump;
     let position_user = ctx.accounts.position.user;
     let position_id = ctx.accounts.position.position_id;
-    let contract_size = ctx.accounts.position.contract_size;
-    let strike_price = ctx.accounts.position.strike_price;
+    let asset_mint = ctx.accounts.position.asset_mint;
+    let user_collateral_mint = ctx.accounts.position.user_collateral_mint();
+    let mm_collateral_mint = ctx.accounts.position.mm_collateral_mint();
+    let in_the_money = ctx
+        .accounts
+        .position
+        .strategy
+        .is_in_the_money(ctx.accounts.position.strike_price, settlement_price);
 
     let position_seeds = &[
         POSITION_SEED,
@@ -147,77 +174,59 @@ fn settle_covered_call(
     ];
     let signer = &[&position_seeds[..]];
 
+    let accounts = &ctx.accounts;
+    let user_account_for = |mint: Pubkey| {
+        if mint == asset_mint {
+            &accounts.user_asset_account
+        } else {
+            &accounts.user_quote_account
+        }
+    };
+    let mm_account_for = |mint: Pubkey| {
+        if mint == asset_mint {
+            &accounts.mm_asset_account
+        } else {
+            &accounts.mm_quote_account
+        }
+    };
 
-    if settlement_price > strike_price {
-        // ITM: MM exercises
-        ctx.accounts.position.status = PositionStatus::SettledITM;
-
-        // Transfer underlying from position_user_vault to MM
-        let cpi_accounts_underlying = Transfer {
-            from: ctx.accounts.position_user_vault.to_account_info(),
-            to: ctx.accounts.mm_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_underlying,
-                signer,
-            ),
-            contract_size,
-        )?;
+    let (user_vault_recipient, mm_vault_recipient) = if in_the_money {
+        (
+            mm_account_for(user_collateral_mint),
+            user_account_for(mm_collateral_mint),
+        )
+    } else {
+        (
+            user_account_for(user_collateral_mint),
+            mm_account_for(mm_collateral_mint),
+        )
+    };
 
-        // Transfer USDC from position_mm_vault to user
-        let cpi_accounts_usdc = Transfer {
-            from: ctx.accounts.position_mm_vault.to_account_info(),
-            to: ctx.accounts.user_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
+    for (vault, recipient) in [
+        (&accounts.position_user_vault, user_vault_recipient),
+        (&accounts.position_mm_vault, mm_vault_recipient),
+    ] {
+        let cpi_accounts = Transfer {
+            from: vault.to_account_info(),
+            to: recipient.to_account_info(),
+            authority: accounts.position_vault_authority.to_account_info(),
         };
         token::transfer(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_usdc,
+                accounts.token_program.to_account_info(),
+                cpi_accounts,
                 signer,
             ),
-            ctx.accounts.position_mm_vault.amount,
+            vault.amount,
         )?;
+    }
 
-        msg!("Covered call settled ITM - MM exercised");
+    if in_the_money {
+        ctx.accounts.position.status = PositionStatus::SettledITM;
+        msg!("Position settled ITM - collateral exchanged");
     } else {
-        // OTM: Expires worthless
         ctx.accounts.position.status = PositionStatus::SettledOTM;
-
-        // Transfer underlying back to user
-        let cpi_accounts_underlying = Transfer {
-            from: ctx.accounts.position_user_vault.to_account_info(),
-            to: ctx.accounts.user_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_underlying,
-                signer,
-            ),
-            contract_size,
-        )?;
-
-        // Transfer USDC back to MM
-        let cpi_accounts_usdc = Transfer {
-            from: ctx.accounts.position_mm_vault.to_account_info(),
-            to: ctx.accounts.mm_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_usdc,
-                signer,
-            ),
-            ctx.accounts.position_mm_vault.amount,
-        )?;
-
-        msg!("Covered call settled OTM - User keeps underlying");
+        msg!("Position settled OTM - collateral returned");
     }
 
     // Update market maker stats
@@ -228,123 +237,134 @@ fn settle_covered_call(
         .ok_or(ErrorCode::MathOverflow)?;
 
     // Unlock MM vault liquidity
-    let mm_vault = &mut ctx.accounts.mm_vault;
-    let strike_amount = strike_price
-        .checked_mul(contract_size)
-        .ok_or(ErrorCode::MathOverflow)?;
-
-    mm_vault.locked_liquidity = mm_vault
-        .locked_liquidity
-        .checked_sub(strike_amount)
-        .ok_or(ErrorCode::MathOverflow)?;
+    let mm_collateral = ctx.accounts.position.mm_collateral;
+    ctx.accounts.mm_vault.unlock(mm_collateral)?;
 
     Ok(())
 }
 
-fn settle_cash_secured_put(
-    ctx: &mut Context<SettlePosition>,
-    settlement_price: u64,
-) -> Result<()> {
-    // Extract position data before any mutable borrows
-    let position_bump = ctx.accounts.position.bump;
-    let position_user = ctx.accounts.position.user;
-    let position_id = ctx.accounts.position.position_id;
-    let contract_size = ctx.accounts.position.contract_size;
-    let strike_price = ctx.accounts.position.strike_price;
+// Close a settled position and return rent for it and its vaults (anyone)
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [POSITION_SEED, position.user.as_ref(), &position.position_id.to_le_bytes()],
+        bump = position.bump,
+        has_one = rent_payer @ ErrorCode::Unauthorized,
+        has_one = market_maker @ ErrorCode::Unauthorized,
+        constraint = position.is_settled() @ ErrorCode::PositionNotSettled
+    )]
+    pub position: Account<'info, Position>,
 
-    let position_seeds = &[
-        POSITION_SEED,
-        position_user.as_ref(),
-        &position_id.to_le_bytes(),
-        &[position_bump],
-    ];
-    let signer = &[&position_seeds[..]];
+    #[account(
+        seeds = [MARKET_MAKER_SEED, market_maker.original_owner.as_ref()],
+        bump = market_maker.bump
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
 
-    if settlement_price < strike_price {
-        // ITM: User exercises
-        ctx.accounts.position.status = PositionStatus::SettledITM;
+    #[account(
+        mut,
+        address = position.user_vault @ ErrorCode::InvalidPositionVault
+    )]
+    pub position_user_vault: Account<'info, TokenAccount>,
 
-        // Transfer underlying from position_mm_vault to user
-        let cpi_accounts_underlying = Transfer {
-            from: ctx.accounts.position_mm_vault.to_account_info(),
-            to: ctx.accounts.user_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_underlying,
-                signer,
-            ),
-            contract_size,
-        )?;
+    #[account(
+        mut,
+        address = position.mm_vault_locked @ ErrorCode::InvalidPositionVault
+    )]
+    pub position_mm_vault: Account<'info, TokenAccount>,
 
-        // Transfer USDC from position_user_vault to MM
-        let cpi_accounts_usdc = Transfer {
-            from: ctx.accounts.position_user_vault.to_account_info(),
-            to: ctx.accounts.mm_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_usdc,
-                signer,
-            ),
-            ctx.accounts.position_user_vault.amount,
-        )?;
+    // Receive any balance sent to the vaults after settlement, owned by
+    // whoever the vault's collateral was settled to
+    #[account(
+        mut,
+        token::mint = position_user_vault.mint,
+        token::authority = position.vault_recipients(market_maker.owner).0
+    )]
+    pub user_vault_recipient: Account<'info, TokenAccount>,
 
-        msg!("Cash secured put settled ITM - User exercised");
-    } else {
-        // OTM: Expires worthless
-        ctx.accounts.position.status = PositionStatus::SettledOTM;
+    #[account(
+        mut,
+        token::mint = position_mm_vault.mint,
+        token::authority = position.vault_recipients(market_maker.owner).1
+    )]
+    pub mm_vault_recipient: Account<'info, TokenAccount>,
 
-        // Transfer USDC back to user
-        let cpi_accounts_usdc = Transfer {
-            from: ctx.accounts.position_user_vault.to_account_info(),
-            to: ctx.accounts.user_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_usdc,
-                signer,
-            ),
-            ctx.accounts.position_user_vault.amount,
-        )?;
+    /// CHECK: Account that paid the position rent (receives refund), checked by has_one
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
 
-        // Transfer underlying back to MM
-        let cpi_accounts_underlying = Transfer {
-            from: ctx.accounts.position_mm_vault.to_account_info(),
-            to: ctx.accounts.mm_destination.to_account_info(),
-            authority: ctx.accounts.position_vault_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_underlying,
-                signer,
-            ),
-            contract_size,
-        )?;
+    /// Anyone can close a settled position
+    pub caller: Signer<'info>,
 
-        msg!("Cash secured put settled OTM - User gets USDC back");
-    }
+    pub token_program: Program<'info, Token>,
+}
 
-    // Update market maker stats
-    let market_maker = &mut ctx.accounts.market_maker;
-    market_maker.completed_positions = market_maker
-        .completed_positions
-        .checked_add(1)
-        .ok_or(ErrorCode::MathOverflow)?;
+pub fn handle_close_position(ctx: Context<ClosePosition>) -> Result<()> {
+    let position = &ctx.accounts.position;
+    let position_id = position.position_id.to_le_bytes();
+    let position_seeds = &[
+        POSITION_SEED,
+        position.user.as_ref(),
+        position_id.as_ref(),
+        &[position.bump],
+    ];
+    let signer = &[&position_seeds[..]];
 
-    // Unlock MM vault liquidity
-    let mm_vault = &mut ctx.accounts.mm_vault;
-    mm_vault.locked_liquidity = mm_vault
-        .locked_liquidity
-        .checked_sub(contract_size)
-        .ok_or(ErrorCode::MathOverflow)?;
+    // The position PDA owns both vaults. Sweep leftovers first so a stray
+    // transfer into a vault cannot block the close.
+    for (vault, recipient) in [
+        (&ctx.accounts.position_user_vault, &ctx.accounts.user_vault_recipient),
+        (&ctx.accounts.position_mm_vault, &ctx.accounts.mm_vault_recipient),
+    ] {
+        if vault.amount > 0 {
+            let cpi_accounts = Transfer {
+                from: vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: position.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer,
+                ),
+                vault.amount,
+            )?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: vault.to_account_info(),
+            destination: ctx.accounts.rent_payer.to_account_info(),
+            authority: position.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        ))?;
+    }
+
+    let (user_collateral_recipient, mm_collateral_recipient) =
+        position.vault_recipients(ctx.accounts.market_maker.owner);
+
+    emit!(PositionClosed {
+        position: position.key(),
+        position_id: position.position_id,
+        user: position.user,
+        market_maker: position.market_maker,
+        status: position.status,
+        settlement_price: position.settlement_price,
+        user_collateral_recipient,
+        user_collateral: position.user_collateral,
+        mm_collateral_recipient,
+        mm_collateral: position.mm_collateral,
+        user_vault_swept: ctx.accounts.position_user_vault.amount,
+        mm_vault_swept: ctx.accounts.position_mm_vault.amount,
+    });
+
+    msg!("Position closed: {}", position.key());
 
     Ok(())
 }
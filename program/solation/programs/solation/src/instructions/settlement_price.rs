@@ -0,0 +1,268 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::oracle;
+
+// Initialize the per-expiry settlement price record (anyone, usually a keeper)
+#[derive(Accounts)]
+#[instruction(asset_mint: Pubkey, expiry_timestamp: i64)]
+pub struct InitializeSettlementPriceRecord<'info> {
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SettlementPriceRecord::LEN,
+        seeds = [SETTLEMENT_PRICE_SEED, asset_mint.as_ref(), &expiry_timestamp.to_le_bytes()],
+        bump
+    )]
+    pub settlement_price_record: Account<'info, SettlementPriceRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_settlement_price_record(
+    ctx: Context<InitializeSettlementPriceRecord>,
+    asset_mint: Pubkey,
+    expiry_timestamp: i64,
+) -> Result<()> {
+    // The record keeps the mode in force when it is made; later asset changes
+    // do not affect this expiry
+    let window_secs = ctx.accounts.asset_config.settlement_mode.window_secs();
+
+    let record = &mut ctx.accounts.settlement_price_record;
+
+    record.asset_mint = asset_mint;
+    record.expiry_timestamp = expiry_timestamp;
+    record.window_secs = window_secs;
+    record.sample_count = 0;
+    record.price_sum = 0;
+    record.filled_buckets = 0;
+    record.settlement_price = None;
+    record.published_at = 0;
+    record.bump = ctx.bumps.settlement_price_record;
+
+    msg!(
+        "Settlement price record initialized for {} at {}",
+        asset_mint,
+        expiry_timestamp
+    );
+
+    Ok(())
+}
+
+// Submit a TWAP price sample (keeper)
+#[derive(Accounts)]
+pub struct SubmitPriceSample<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, settlement_price_record.asset_mint.as_ref()],
+        bump = asset_config.bump,
+        constraint = !asset_config.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::AssetPaused
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            SETTLEMENT_PRICE_SEED,
+            settlement_price_record.asset_mint.as_ref(),
+            &settlement_price_record.expiry_timestamp.to_le_bytes()
+        ],
+        bump = settlement_price_record.bump
+    )]
+    pub settlement_price_record: Account<'info, SettlementPriceRecord>,
+
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::Keeper) == keeper.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    pub keeper: Signer<'info>,
+}
+
+pub fn handle_submit_price_sample(ctx: Context<SubmitPriceSample>) -> Result<()> {
+    let (price, publish_time) =
+        oracle::get_verified_price(&ctx.accounts.price_update, &ctx.accounts.asset_config)?;
+
+    // Only prints from the TWAP window leading up to expiry count, one per slot
+    let record = &mut ctx.accounts.settlement_price_record;
+    record.add_sample(price, publish_time)?;
+
+    msg!(
+        "Price sample {} recorded: {} (sample {})",
+        publish_time,
+        price,
+        record.sample_count
+    );
+
+    Ok(())
+}
+
+// Publish the expiry price shared by every position on this asset and expiry
+// (keeper for spot settlement, anyone for a TWAP)
+#[derive(Accounts)]
+#[instruction(asset_mint: Pubkey, expiry_timestamp: i64)]
+pub struct PublishSettlementPrice<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = !global_state.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::ProtocolPaused
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, asset_mint.as_ref()],
+        bump = asset_config.bump,
+        constraint = !asset_config.is_paused(PAUSE_SETTLEMENT) @ ErrorCode::AssetPaused
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_PRICE_SEED, asset_mint.as_ref(), &expiry_timestamp.to_le_bytes()],
+        bump = settlement_price_record.bump
+    )]
+    pub settlement_price_record: Account<'info, SettlementPriceRecord>,
+
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    // Pyth price update just before expiry, required for spot settlement
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+
+    pub publisher: Signer<'info>,
+}
+
+pub fn handle_publish_settlement_price(
+    ctx: Context<PublishSettlementPrice>,
+    _asset_mint: Pubkey,
+    expiry_timestamp: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        clock.unix_timestamp >= expiry_timestamp,
+        ErrorCode::PositionNotExpired
+    );
+
+    // The record's mode, not the asset's current one, decides how this expiry settles
+    let settlement_price = match ctx.accounts.settlement_price_record.settlement_mode() {
+        // Last print at or before expiry, not around now. Late publishers pass a
+        // historical update posted for the expiry timestamp. The single print is
+        // final, so only the keeper may choose it.
+        SettlementMode::Spot => {
+            require_keys_eq!(
+                ctx.accounts.publisher.key(),
+                ctx.accounts.role_registry.holder(Role::Keeper),
+                ErrorCode::Unauthorized
+            );
+            let price_update = ctx
+                .accounts
+                .price_update
+                .as_ref()
+                .ok_or(ErrorCode::MissingSettlementPriceAccount)?;
+            oracle::get_price_at(price_update, &ctx.accounts.asset_config, expiry_timestamp)?
+        }
+        // Average of the keeper samples taken over the window before expiry
+        SettlementMode::Twap { .. } => ctx.accounts.settlement_price_record.twap()?,
+    };
+
+    ctx.accounts
+        .settlement_price_record
+        .publish(settlement_price, clock.unix_timestamp)?;
+
+    msg!(
+        "Settlement price published for expiry {}: {}",
+        expiry_timestamp,
+        settlement_price
+    );
+
+    Ok(())
+}
+
+// Publish a price for an expiry still unpublished a day after it passed, so
+// positions never stay locked on a record that cannot publish (super-admin)
+#[derive(Accounts)]
+#[instruction(asset_mint: Pubkey, expiry_timestamp: i64)]
+pub struct PublishFallbackSettlementPrice<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_PRICE_SEED, asset_mint.as_ref(), &expiry_timestamp.to_le_bytes()],
+        bump = settlement_price_record.bump
+    )]
+    pub settlement_price_record: Account<'info, SettlementPriceRecord>,
+
+    // Pyth price update just before expiry, the fallback still settles on an oracle print
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handle_publish_fallback_settlement_price(
+    ctx: Context<PublishFallbackSettlementPrice>,
+    _asset_mint: Pubkey,
+    expiry_timestamp: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts
+            .settlement_price_record
+            .fallback_open(clock.unix_timestamp)?,
+        ErrorCode::SettlementFallbackNotOpen
+    );
+
+    let settlement_price = oracle::get_price_at(
+        &ctx.accounts.price_update,
+        &ctx.accounts.asset_config,
+        expiry_timestamp,
+    )?;
+
+    ctx.accounts
+        .settlement_price_record
+        .publish(settlement_price, clock.unix_timestamp)?;
+
+    msg!(
+        "Fallback settlement price published for expiry {}: {}",
+        expiry_timestamp,
+        settlement_price
+    );
+
+    Ok(())
+}
@@ -0,0 +1,338 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::events::*;
+
+// Queue a protocol fee change (fee manager)
+#[derive(Accounts)]
+pub struct QueueProtocolFeeChange<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::FeeManager) == fee_manager.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(
+        init,
+        payer = fee_manager,
+        space = PendingChange::LEN,
+        seeds = [PENDING_CHANGE_SEED, global_state.key().as_ref()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
+    pub fee_manager: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_queue_protocol_fee_change(
+    ctx: Context<QueueProtocolFeeChange>,
+    protocol_fee_bps: u16,
+) -> Result<()> {
+    let change = ParameterChange::ProtocolFee { protocol_fee_bps };
+    let mut preview = (*ctx.accounts.global_state).clone();
+    change.apply_to_global(&mut preview)?;
+
+    let target = ctx.accounts.global_state.key();
+    let delay = ctx.accounts.global_state.timelock_delay_seconds;
+    queue_change(
+        &mut ctx.accounts.pending_change,
+        target,
+        change,
+        ctx.accounts.fee_manager.key(),
+        delay,
+        ctx.bumps.pending_change,
+    )
+}
+
+// Queue a GlobalState change other than the protocol fee (super-admin)
+#[derive(Accounts)]
+pub struct QueueGlobalChange<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = PendingChange::LEN,
+        seeds = [PENDING_CHANGE_SEED, global_state.key().as_ref()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_queue_timelock_delay_change(
+    ctx: Context<QueueGlobalChange>,
+    delay_seconds: i64,
+) -> Result<()> {
+    // The current delay applies, so shortening it still gives full notice
+    queue_global_change(ctx, ParameterChange::TimelockDelay { delay_seconds })
+}
+
+pub fn handle_queue_treasury_change(ctx: Context<QueueGlobalChange>, treasury: Pubkey) -> Result<()> {
+    queue_global_change(ctx, ParameterChange::Treasury { treasury })
+}
+
+pub fn handle_queue_max_protocol_fee_change(
+    ctx: Context<QueueGlobalChange>,
+    max_protocol_fee_bps: u16,
+) -> Result<()> {
+    queue_global_change(ctx, ParameterChange::MaxProtocolFee { max_protocol_fee_bps })
+}
+
+pub fn handle_queue_max_price_age_change(
+    ctx: Context<QueueGlobalChange>,
+    max_price_age_seconds: u64,
+) -> Result<()> {
+    queue_global_change(ctx, ParameterChange::MaxPriceAge { max_price_age_seconds })
+}
+
+fn queue_global_change(ctx: Context<QueueGlobalChange>, change: ParameterChange) -> Result<()> {
+    // Reject invalid changes now rather than at execution
+    let mut preview = (*ctx.accounts.global_state).clone();
+    change.apply_to_global(&mut preview)?;
+
+    let target = ctx.accounts.global_state.key();
+    let delay = ctx.accounts.global_state.timelock_delay_seconds;
+    queue_change(
+        &mut ctx.accounts.pending_change,
+        target,
+        change,
+        ctx.accounts.authority.key(),
+        delay,
+        ctx.bumps.pending_change,
+    )
+}
+
+// Queue an asset config update (asset manager)
+#[derive(Accounts)]
+pub struct QueueAssetUpdate<'info> {
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+        constraint = role_registry.holder(Role::AssetManager) == asset_manager.key() @ ErrorCode::Unauthorized
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(
+        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        init,
+        payer = asset_manager,
+        space = PendingChange::LEN,
+        seeds = [PENDING_CHANGE_SEED, asset_config.key().as_ref()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    // New quote mint, required when the update changes it
+    pub quote_mint_account: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub asset_manager: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_queue_asset_update(ctx: Context<QueueAssetUpdate>, update: AssetUpdate) -> Result<()> {
+    // Reject invalid updates now rather than at execution
+    let mut preview = (*ctx.accounts.asset_config).clone();
+    update.apply(&mut preview, mint_info(&ctx.accounts.quote_mint_account))?;
+
+    let target = ctx.accounts.asset_config.key();
+    let delay = ctx.accounts.global_state.timelock_delay_seconds;
+    queue_change(
+        &mut ctx.accounts.pending_change,
+        target,
+        ParameterChange::Asset(update),
+        ctx.accounts.asset_manager.key(),
+        delay,
+        ctx.bumps.pending_change,
+    )
+}
+
+// Apply a queued change after its eta (anyone)
+#[derive(Accounts)]
+pub struct ExecutePendingChange<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_CHANGE_SEED, pending_change.target.as_ref()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    // Required for asset updates
+    #[account(
+        mut,
+        seeds = [ASSET_CONFIG_SEED, asset_config.asset_mint.as_ref()],
+        bump = asset_config.bump
+    )]
+    pub asset_config: Option<Account<'info, AssetConfig>>,
+
+    // New quote mint, required when an asset update changes it
+    pub quote_mint_account: Option<Account<'info, Mint>>,
+
+    /// CHECK: Receives the pending change rent, must be its proposer
+    #[account(
+        mut,
+        address = pending_change.proposer @ ErrorCode::Unauthorized
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    pub executor: Signer<'info>,
+}
+
+pub fn handle_execute_pending_change(ctx: Context<ExecutePendingChange>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending_change = &ctx.accounts.pending_change;
+
+    require!(
+        pending_change.is_ready(clock.unix_timestamp),
+        ErrorCode::ChangeNotReady
+    );
+
+    match &pending_change.change {
+        ParameterChange::Asset(update) => {
+            let asset_config = ctx
+                .accounts
+                .asset_config
+                .as_mut()
+                .ok_or(ErrorCode::InvalidChangeTarget)?;
+            require!(
+                pending_change.target == asset_config.key(),
+                ErrorCode::InvalidChangeTarget
+            );
+            update.apply(asset_config, mint_info(&ctx.accounts.quote_mint_account))?;
+        }
+        change => {
+            let global_state = &mut ctx.accounts.global_state;
+            require!(
+                pending_change.target == global_state.key(),
+                ErrorCode::InvalidChangeTarget
+            );
+            change.apply_to_global(global_state)?;
+        }
+    }
+
+    emit!(ParameterChangeExecuted {
+        target: pending_change.target,
+        change: pending_change.change.clone(),
+    });
+
+    msg!("Parameter change executed for: {}", pending_change.target);
+
+    Ok(())
+}
+
+// Drop a queued change (its proposer or the super-admin)
+#[derive(Accounts)]
+pub struct CancelPendingChange<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_CHANGE_SEED, pending_change.target.as_ref()],
+        bump = pending_change.bump,
+        constraint = canceller.key() == pending_change.proposer
+            || canceller.key() == global_state.authority @ ErrorCode::Unauthorized
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        seeds = [GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// CHECK: Receives the pending change rent, must be its proposer
+    #[account(
+        mut,
+        address = pending_change.proposer @ ErrorCode::Unauthorized
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    pub canceller: Signer<'info>,
+}
+
+pub fn handle_cancel_pending_change(ctx: Context<CancelPendingChange>) -> Result<()> {
+    let target = ctx.accounts.pending_change.target;
+
+    emit!(ParameterChangeCancelled { target });
+
+    msg!("Parameter change cancelled for: {}", target);
+
+    Ok(())
+}
+
+fn queue_change(
+    pending_change: &mut Account<PendingChange>,
+    target: Pubkey,
+    change: ParameterChange,
+    proposer: Pubkey,
+    delay_seconds: i64,
+    bump: u8,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let eta = clock
+        .unix_timestamp
+        .checked_add(delay_seconds)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    pending_change.target = target;
+    pending_change.change = change;
+    pending_change.proposer = proposer;
+    pending_change.queued_at = clock.unix_timestamp;
+    pending_change.eta = eta;
+    pending_change.bump = bump;
+
+    emit!(ParameterChangeQueued {
+        target,
+        change: pending_change.change.clone(),
+        eta,
+    });
+
+    msg!("Parameter change queued for {}, executable at {}", target, eta);
+
+    Ok(())
+}
+
+fn mint_info(mint: &Option<Account<Mint>>) -> Option<(Pubkey, u8)> {
+    mint.as_ref().map(|m| (m.key(), m.decimals))
+}
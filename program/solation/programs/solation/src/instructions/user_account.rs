@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+
+// Create the user's account (one-time, before the first request)
+#[derive(Accounts)]
+pub struct InitializeUserAccount<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = UserAccount::LEN,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_user_account(ctx: Context<InitializeUserAccount>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+
+    user_account.user = ctx.accounts.user.key();
+    user_account.next_request_id = 0;
+    user_account.next_position_id = 0;
+    user_account.open_positions = 0;
+    user_account.lifetime_premium_earned = 0;
+    user_account.notional_traded = 0;
+    user_account.bump = ctx.bumps.user_account;
+
+    msg!("User account initialized: {}", user_account.user);
+
+    Ok(())
+}
@@ -2,7 +2,10 @@ use anchor_lang::prelude::*;
 
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
+pub mod math;
+pub mod oracle;
 pub mod state;
 
 use instructions::*;
@@ -16,27 +19,45 @@ pub mod solation {
 
     // ===== Admin Instructions =====
 
+    /// Upgrade authority creates the global state with the full deployment config
     pub fn initialize_global_state(
         ctx: Context<InitializeGlobalState>,
-        protocol_fee_bps: u16,
+        config: GlobalStateConfig,
     ) -> Result<()> {
-        instructions::handle_initialize_global_state(ctx, protocol_fee_bps)
+        instructions::handle_initialize_global_state(ctx, config)
     }
 
-    pub fn update_global_state(
-        ctx: Context<UpdateGlobalState>,
-        new_authority: Option<Pubkey>,
-        new_treasury: Option<Pubkey>,
-        new_fee_bps: Option<u16>,
-        paused: Option<bool>,
-    ) -> Result<()> {
-        instructions::handle_update_global_state(
-            ctx,
-            new_authority,
-            new_treasury,
-            new_fee_bps,
-            paused,
-        )
+    /// Super-admin assigns the pauser, asset manager, fee manager or keeper role
+    pub fn set_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+        instructions::handle_set_role(ctx, role, holder)
+    }
+
+    /// Pauser toggles PAUSE_* instruction classes protocol-wide, effective immediately
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, pause_flags: u8) -> Result<()> {
+        instructions::handle_set_pause_flags(ctx, pause_flags)
+    }
+
+    /// Pauser toggles requests, confirms, quoting or settlement for one asset
+    pub fn set_asset_pause_flags(ctx: Context<SetAssetPauseFlags>, pause_flags: u8) -> Result<()> {
+        instructions::handle_set_asset_pause_flags(ctx, pause_flags)
+    }
+
+    /// Pauser disables an asset at once; re-enabling is a timelocked asset update
+    pub fn disable_asset(ctx: Context<DisableAsset>) -> Result<()> {
+        instructions::handle_disable_asset(ctx)
+    }
+
+    /// Authority proposes a successor, who must accept before control moves
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        instructions::handle_propose_authority(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::handle_accept_authority(ctx)
+    }
+
+    pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+        instructions::handle_cancel_authority_transfer(ctx)
     }
 
     pub fn add_asset(
@@ -48,7 +69,9 @@ pub mod solation {
         max_strike_percentage: u16,
         min_expiry_seconds: i64,
         max_expiry_seconds: i64,
-        decimals: u8,
+        max_confidence_bps: u16,
+        settlement_window_seconds: i64,
+        settlement_mode: SettlementMode,
     ) -> Result<()> {
         instructions::handle_add_asset(
             ctx,
@@ -59,26 +82,64 @@ pub mod solation {
             max_strike_percentage,
             min_expiry_seconds,
             max_expiry_seconds,
-            decimals,
+            max_confidence_bps,
+            settlement_window_seconds,
+            settlement_mode,
         )
     }
 
-    pub fn update_asset(
-        ctx: Context<UpdateAsset>,
-        enabled: Option<bool>,
-        min_strike_percentage: Option<u16>,
-        max_strike_percentage: Option<u16>,
-        min_expiry_seconds: Option<i64>,
-        max_expiry_seconds: Option<i64>,
+    // ===== Timelocked Parameter Changes =====
+
+    /// Fee manager queues a protocol fee change, executable after the timelock
+    pub fn queue_protocol_fee_change(
+        ctx: Context<QueueProtocolFeeChange>,
+        protocol_fee_bps: u16,
     ) -> Result<()> {
-        instructions::handle_update_asset(
-            ctx,
-            enabled,
-            min_strike_percentage,
-            max_strike_percentage,
-            min_expiry_seconds,
-            max_expiry_seconds,
-        )
+        instructions::handle_queue_protocol_fee_change(ctx, protocol_fee_bps)
+    }
+
+    /// Authority queues a change to the timelock delay itself
+    pub fn queue_timelock_delay_change(
+        ctx: Context<QueueGlobalChange>,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        instructions::handle_queue_timelock_delay_change(ctx, delay_seconds)
+    }
+
+    /// Authority queues a new fee recipient
+    pub fn queue_treasury_change(ctx: Context<QueueGlobalChange>, treasury: Pubkey) -> Result<()> {
+        instructions::handle_queue_treasury_change(ctx, treasury)
+    }
+
+    /// Authority queues a new protocol fee ceiling, at most MAX_PROTOCOL_FEE_BPS
+    pub fn queue_max_protocol_fee_change(
+        ctx: Context<QueueGlobalChange>,
+        max_protocol_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::handle_queue_max_protocol_fee_change(ctx, max_protocol_fee_bps)
+    }
+
+    /// Authority queues a new maximum Pyth price age for quoting
+    pub fn queue_max_price_age_change(
+        ctx: Context<QueueGlobalChange>,
+        max_price_age_seconds: u64,
+    ) -> Result<()> {
+        instructions::handle_queue_max_price_age_change(ctx, max_price_age_seconds)
+    }
+
+    /// Asset manager queues an asset config update (risk params, oracle feed, quote mint)
+    pub fn queue_asset_update(ctx: Context<QueueAssetUpdate>, update: AssetUpdate) -> Result<()> {
+        instructions::handle_queue_asset_update(ctx, update)
+    }
+
+    /// Anyone can apply a queued change once its eta has passed
+    pub fn execute_pending_change(ctx: Context<ExecutePendingChange>) -> Result<()> {
+        instructions::handle_execute_pending_change(ctx)
+    }
+
+    /// Proposer or authority can drop a queued change before it executes
+    pub fn cancel_pending_change(ctx: Context<CancelPendingChange>) -> Result<()> {
+        instructions::handle_cancel_pending_change(ctx)
     }
 
     // ===== Market Maker Instructions =====
@@ -87,6 +148,24 @@ pub mod solation {
         instructions::handle_register_market_maker(ctx)
     }
 
+    /// Owner proposes a new custody key; vaults and quotes stay with the MM account
+    pub fn propose_market_maker_owner(
+        ctx: Context<ProposeMarketMakerOwner>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::handle_propose_market_maker_owner(ctx, new_owner)
+    }
+
+    pub fn accept_market_maker_owner(ctx: Context<AcceptMarketMakerOwner>) -> Result<()> {
+        instructions::handle_accept_market_maker_owner(ctx)
+    }
+
+    pub fn cancel_market_maker_owner_transfer(
+        ctx: Context<CancelMarketMakerOwnerTransfer>,
+    ) -> Result<()> {
+        instructions::handle_cancel_market_maker_owner_transfer(ctx)
+    }
+
     pub fn initialize_vault(ctx: Context<InitializeVault>, asset_mint: Pubkey) -> Result<()> {
         instructions::handle_initialize_vault(ctx, asset_mint)
     }
@@ -105,7 +184,8 @@ pub mod solation {
         quote_mint: Pubkey,
         strategy: StrategyType,
         strikes: Vec<StrikeQuote>,
-        expiry_timestamp: i64,
+        valid_until: i64,
+        option_expiry: i64,
         min_size: u64,
         max_size: u64,
     ) -> Result<()> {
@@ -115,16 +195,18 @@ pub mod solation {
             quote_mint,
             strategy,
             strikes,
-            expiry_timestamp,
+            valid_until,
+            option_expiry,
             min_size,
             max_size,
         )
     }
 
+    /// Option expiry is fixed at submission; deactivate and submit a new quote to move it
     pub fn update_quote(
         ctx: Context<UpdateQuote>,
         strikes: Option<Vec<StrikeQuote>>,
-        expiry_timestamp: Option<i64>,
+        valid_until: Option<i64>,
         min_size: Option<u64>,
         max_size: Option<u64>,
         active: Option<bool>,
@@ -132,43 +214,102 @@ pub mod solation {
         instructions::handle_update_quote(
             ctx,
             strikes,
-            expiry_timestamp,
+            valid_until,
             min_size,
             max_size,
             active,
         )
     }
 
+    /// MM closes a quote with no pending requests and reclaims its rent
+    pub fn close_quote(ctx: Context<CloseQuote>) -> Result<()> {
+        instructions::handle_close_quote(ctx)
+    }
+
+    /// Anyone can close a quote past valid_until with no pending requests - rent goes to the MM
+    pub fn close_expired_quote(ctx: Context<CloseExpiredQuote>) -> Result<()> {
+        instructions::handle_close_expired_quote(ctx)
+    }
+
+    // ===== User Instructions =====
+
+    /// User creates the account that numbers their requests and positions
+    pub fn initialize_user_account(ctx: Context<InitializeUserAccount>) -> Result<()> {
+        instructions::handle_initialize_user_account(ctx)
+    }
+
     // ===== Position Request Instructions (Two-Phase Commit) =====
 
-    /// User requests a position - creates pending request for MM to approve
+    /// User requests a position - escrows collateral and creates pending request for MM to approve
     pub fn request_position(
         ctx: Context<RequestPosition>,
-        request_id: u64,
         strike_price: u64,
         contract_size: u64,
     ) -> Result<()> {
-        instructions::handle_request_position(ctx, request_id, strike_price, contract_size)
+        instructions::handle_request_position(ctx, strike_price, contract_size)
     }
 
-    /// MM confirms the request within 30 seconds - locks collateral and pays premium
-    pub fn confirm_position(ctx: Context<ConfirmPosition>, position_id: u64) -> Result<()> {
-        instructions::handle_confirm_position(ctx, position_id)
+    /// MM confirms the request within 30 seconds - locks collateral, pays premium, opens the user's next position and closes the request
+    pub fn confirm_position(ctx: Context<ConfirmPosition>) -> Result<()> {
+        instructions::handle_confirm_position(ctx)
     }
 
-    /// MM explicitly rejects the request
+    /// MM explicitly rejects the request - escrow is refunded to the user
     pub fn reject_request(ctx: Context<RejectRequest>) -> Result<()> {
         instructions::handle_reject_request(ctx)
     }
 
-    /// Anyone can cancel expired requests (after 30s timeout)
+    /// User cancels their own pending request - escrow is refunded
+    pub fn cancel_request(ctx: Context<CancelRequest>) -> Result<()> {
+        instructions::handle_cancel_request(ctx)
+    }
+
+    /// Anyone can cancel expired requests (after 30s timeout) - escrow is refunded to the user
     pub fn cancel_expired_request(ctx: Context<CancelExpiredRequest>) -> Result<()> {
         instructions::handle_cancel_expired_request(ctx)
     }
 
     // ===== Settlement Instructions =====
 
+    /// Anyone can settle after expiry against the published settlement price for that expiry
     pub fn settle_position(ctx: Context<SettlePosition>) -> Result<()> {
         instructions::handle_settle_position(ctx)
     }
+
+    /// Anyone can close a settled position - rent goes back to whoever paid it
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        instructions::handle_close_position(ctx)
+    }
+
+    /// Anyone can create the price record for an asset expiry
+    pub fn initialize_settlement_price_record(
+        ctx: Context<InitializeSettlementPriceRecord>,
+        asset_mint: Pubkey,
+        expiry_timestamp: i64,
+    ) -> Result<()> {
+        instructions::handle_initialize_settlement_price_record(ctx, asset_mint, expiry_timestamp)
+    }
+
+    /// Keeper adds a Pyth print to an unsampled slot of the TWAP window before expiry
+    pub fn submit_price_sample(ctx: Context<SubmitPriceSample>) -> Result<()> {
+        instructions::handle_submit_price_sample(ctx)
+    }
+
+    /// Fixes the expiry price once (keeper for spot, anyone for a TWAP); all positions on that expiry settle against it
+    pub fn publish_settlement_price(
+        ctx: Context<PublishSettlementPrice>,
+        asset_mint: Pubkey,
+        expiry_timestamp: i64,
+    ) -> Result<()> {
+        instructions::handle_publish_settlement_price(ctx, asset_mint, expiry_timestamp)
+    }
+
+    /// Super-admin publishes an oracle price for an expiry left unpublished past the fallback delay
+    pub fn publish_fallback_settlement_price(
+        ctx: Context<PublishFallbackSettlementPrice>,
+        asset_mint: Pubkey,
+        expiry_timestamp: i64,
+    ) -> Result<()> {
+        instructions::handle_publish_fallback_settlement_price(ctx, asset_mint, expiry_timestamp)
+    }
 }
@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::StrategyType;
+
+// Prices (strikes, premiums) are quoted in quote-mint base units per one whole
+// unit of the asset, and contract sizes are in asset base units. All products
+// go through u128 so a large strike times a large size cannot overflow.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down, // Amounts paid out (premium, settlement proceeds)
+    Up,   // Amounts locked as collateral
+}
+
+/// Token amounts each side of a position has to lock
+#[derive(Clone, Copy, Debug)]
+pub struct Collateral {
+    pub user: u64,
+    pub mm: u64,
+}
+
+pub fn pow10(exponent: u32) -> Result<u128> {
+    10u128
+        .checked_pow(exponent)
+        .ok_or_else(|| ErrorCode::MathOverflow.into())
+}
+
+/// `a * b / divisor` with a u128 intermediate and explicit rounding
+pub fn mul_div(a: u64, b: u64, divisor: u128, rounding: Rounding) -> Result<u64> {
+    require!(divisor > 0, ErrorCode::MathOverflow);
+
+    let product = (a as u128)
+        .checked_mul(b as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let mut result = product / divisor;
+    if rounding == Rounding::Up && product % divisor != 0 {
+        result = result.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    u64::try_from(result).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Converts `amount` from `from_decimals` to `to_decimals` precision
+pub fn rescale(amount: u64, from_decimals: u8, to_decimals: u8, rounding: Rounding) -> Result<u64> {
+    if to_decimals >= from_decimals {
+        let factor = pow10((to_decimals - from_decimals) as u32)?;
+        let scaled = (amount as u128)
+            .checked_mul(factor)
+            .ok_or(ErrorCode::MathOverflow)?;
+        u64::try_from(scaled).map_err(|_| ErrorCode::MathOverflow.into())
+    } else {
+        let divisor = pow10((from_decimals - to_decimals) as u32)?;
+        mul_div(amount, 1, divisor, rounding)
+    }
+}
+
+/// Quote-mint value of `contract_size` asset base units at `price`
+pub fn quote_value(
+    price: u64,
+    contract_size: u64,
+    asset_decimals: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    mul_div(price, contract_size, pow10(asset_decimals as u32)?, rounding)
+}
+
+/// Premium owed to the user for `contract_size`, rounded down
+pub fn premium_amount(
+    premium_per_contract: u64,
+    contract_size: u64,
+    asset_decimals: u8,
+) -> Result<u64> {
+    quote_value(premium_per_contract, contract_size, asset_decimals, Rounding::Down)
+}
+
+/// Collateral locked by each side of a position, with the strike notional rounded up
+///
+/// - Covered call: user locks the underlying, MM locks the strike notional in the quote mint
+/// - Cash secured put: user locks the strike notional in the quote mint, MM locks the underlying
+pub fn position_collateral(
+    strategy: StrategyType,
+    strike_price: u64,
+    contract_size: u64,
+    asset_decimals: u8,
+) -> Result<Collateral> {
+    let notional = quote_value(strike_price, contract_size, asset_decimals, Rounding::Up)?;
+
+    Ok(match strategy {
+        StrategyType::CoveredCall => Collateral {
+            user: contract_size,
+            mm: notional,
+        },
+        StrategyType::CashSecuredPut => Collateral {
+            user: notional,
+            mm: contract_size,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overflow() -> Error {
+        ErrorCode::MathOverflow.into()
+    }
+
+    #[test]
+    fn mul_div_rounds_in_the_requested_direction() {
+        assert_eq!(mul_div(10, 3, 4, Rounding::Down).unwrap(), 7);
+        assert_eq!(mul_div(10, 3, 4, Rounding::Up).unwrap(), 8);
+        // Exact results are never bumped
+        assert_eq!(mul_div(8, 3, 4, Rounding::Up).unwrap(), 6);
+    }
+
+    #[test]
+    fn mul_div_zero_and_one() {
+        assert_eq!(mul_div(0, 7, 3, Rounding::Up).unwrap(), 0);
+        assert_eq!(mul_div(1, 1, 3, Rounding::Down).unwrap(), 0);
+        assert_eq!(mul_div(1, 1, 3, Rounding::Up).unwrap(), 1);
+        assert_eq!(mul_div(5, 1, 1, Rounding::Down).unwrap(), 5);
+        assert_eq!(mul_div(5, 1, 0, Rounding::Down).unwrap_err(), overflow());
+    }
+
+    #[test]
+    fn mul_div_uses_a_wide_intermediate() {
+        assert_eq!(
+            mul_div(u64::MAX, u64::MAX, u64::MAX as u128, Rounding::Down).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down).unwrap_err(), overflow());
+        assert_eq!(mul_div(u64::MAX, 1, 1, Rounding::Up).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn rescale_down_rounds() {
+        // 9 -> 6 decimals
+        assert_eq!(rescale(1_234_567_891, 9, 6, Rounding::Down).unwrap(), 1_234_567);
+        assert_eq!(rescale(1_234_567_891, 9, 6, Rounding::Up).unwrap(), 1_234_568);
+        assert_eq!(rescale(1_234_567_000, 9, 6, Rounding::Up).unwrap(), 1_234_567);
+        assert_eq!(rescale(999, 9, 6, Rounding::Down).unwrap(), 0);
+        assert_eq!(rescale(1, 9, 6, Rounding::Up).unwrap(), 1);
+    }
+
+    #[test]
+    fn rescale_up_is_exact() {
+        // 6 -> 9 decimals
+        assert_eq!(rescale(1_234_567, 6, 9, Rounding::Down).unwrap(), 1_234_567_000);
+        assert_eq!(rescale(1_234_567, 6, 9, Rounding::Up).unwrap(), 1_234_567_000);
+        assert_eq!(rescale(42, 6, 6, Rounding::Up).unwrap(), 42);
+        assert_eq!(rescale(0, 0, 18, Rounding::Down).unwrap(), 0);
+    }
+
+    #[test]
+    fn rescale_overflow() {
+        assert_eq!(rescale(u64::MAX, 0, 1, Rounding::Down).unwrap_err(), overflow());
+        assert_eq!(rescale(1, 0, 39, Rounding::Down).unwrap_err(), overflow());
+        assert_eq!(rescale(1, 39, 0, Rounding::Down).unwrap_err(), overflow());
+    }
+
+    #[test]
+    fn quote_value_prices_whole_asset_units() {
+        // 150 USDC (6 decimals) per SOL (9 decimals), 1.5 SOL
+        assert_eq!(
+            quote_value(150_000_000, 1_500_000_000, 9, Rounding::Down).unwrap(),
+            225_000_000
+        );
+        // One lamport at 1 base unit per SOL is dust either way
+        assert_eq!(quote_value(1, 1, 9, Rounding::Down).unwrap(), 0);
+        assert_eq!(quote_value(1, 1, 9, Rounding::Up).unwrap(), 1);
+        assert_eq!(quote_value(0, 1_000_000_000, 9, Rounding::Up).unwrap(), 0);
+    }
+
+    #[test]
+    fn premium_rounds_down() {
+        assert_eq!(premium_amount(3, 1_500_000_000, 9).unwrap(), 4);
+        assert_eq!(premium_amount(2_000_000, 1, 9).unwrap(), 0);
+    }
+
+    #[test]
+    fn collateral_rounds_notional_up() {
+        let call = position_collateral(StrategyType::CoveredCall, 3, 1_500_000_000, 9).unwrap();
+        assert_eq!(call.user, 1_500_000_000);
+        assert_eq!(call.mm, 5);
+
+        let put = position_collateral(StrategyType::CashSecuredPut, 3, 1_500_000_000, 9).unwrap();
+        assert_eq!(put.user, 5);
+        assert_eq!(put.mm, 1_500_000_000);
+    }
+
+    #[test]
+    fn collateral_overflow() {
+        assert_eq!(
+            position_collateral(StrategyType::CoveredCall, u64::MAX, u64::MAX, 0).unwrap_err(),
+            overflow()
+        );
+    }
+}
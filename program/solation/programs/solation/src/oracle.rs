@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::error::GetPriceError;
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2, VerificationLevel};
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::math::{self, Rounding};
+use crate::state::AssetConfig;
+
+/// Reads the asset's price from a fully verified Pyth update no older than
+/// `maximum_age` seconds, scaled to the asset's quote mint decimals
+pub fn get_price(
+    price_update: &PriceUpdateV2,
+    asset_config: &AssetConfig,
+    clock: &Clock,
+    maximum_age: u64,
+) -> Result<u64> {
+    let price = price_update
+        .get_price_no_older_than(clock, maximum_age, &asset_config.pyth_feed_id)
+        .map_err(map_price_error)?;
+
+    normalize_price(&price, asset_config)
+}
+
+/// Reads the asset's price from a fully verified Pyth update published at or
+/// before `target_timestamp`, no earlier than the asset's settlement window.
+/// Prints after the target are rejected so the price cannot be chosen with
+/// hindsight. Freshness relative to the current clock is deliberately not
+/// checked, so an update posted later for a historical timestamp is accepted.
+pub fn get_price_at(
+    price_update: &PriceUpdateV2,
+    asset_config: &AssetConfig,
+    target_timestamp: i64,
+) -> Result<u64> {
+    let (price, publish_time) = get_verified_price(price_update, asset_config)?;
+
+    require!(
+        publish_time <= target_timestamp
+            && target_timestamp - publish_time <= asset_config.settlement_window_seconds,
+        ErrorCode::PriceOutsideSettlementWindow
+    );
+
+    Ok(price)
+}
+
+/// Reads a fully verified price of any age, returning it scaled to quote mint
+/// decimals together with its publish time. Callers must bound the time.
+pub fn get_verified_price(
+    price_update: &PriceUpdateV2,
+    asset_config: &AssetConfig,
+) -> Result<(u64, i64)> {
+    require!(
+        price_update.verification_level.gte(VerificationLevel::Full),
+        ErrorCode::PriceNotVerified
+    );
+
+    let price = price_update
+        .get_price_unchecked(&asset_config.pyth_feed_id)
+        .map_err(map_price_error)?;
+
+    Ok((normalize_price(&price, asset_config)?, price.publish_time))
+}
+
+/// Validates a Pyth price against the asset's confidence bound and converts
+/// `price * 10^exponent` into quote mint base units, rounding down
+pub fn normalize_price(price: &Price, asset_config: &AssetConfig) -> Result<u64> {
+    require!(price.price > 0, ErrorCode::InvalidOraclePrice);
+    let raw_price = price.price as u64;
+
+    // conf / price must not exceed max_confidence_bps
+    let max_conf = math::mul_div(
+        raw_price,
+        asset_config.max_confidence_bps as u64,
+        BASIS_POINTS_DIVISOR as u128,
+        Rounding::Down,
+    )?;
+    require!(price.conf <= max_conf, ErrorCode::PriceConfidenceTooWide);
+
+    if price.exponent <= 0 {
+        let price_decimals =
+            u8::try_from(-price.exponent).map_err(|_| ErrorCode::InvalidOraclePrice)?;
+        math::rescale(
+            raw_price,
+            price_decimals,
+            asset_config.quote_decimals,
+            Rounding::Down,
+        )
+    } else {
+        let exponent = u8::try_from(price.exponent).map_err(|_| ErrorCode::InvalidOraclePrice)?;
+        let target_decimals = asset_config
+            .quote_decimals
+            .checked_add(exponent)
+            .ok_or(ErrorCode::MathOverflow)?;
+        math::rescale(raw_price, 0, target_decimals, Rounding::Down)
+    }
+}
+
+fn map_price_error(error: GetPriceError) -> Error {
+    match error {
+        GetPriceError::PriceTooOld => ErrorCode::PriceTooStale.into(),
+        GetPriceError::InsufficientVerificationLevel => ErrorCode::PriceNotVerified.into(),
+        _ => ErrorCode::PythFeedIdMismatch.into(),
+    }
+}
@@ -1,4 +1,24 @@
 use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::math::{self, Rounding};
+use crate::state::StrikeQuote;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettlementMode {
+    Spot,                        // Single Pyth print near expiry
+    Twap { window_secs: u32 },   // Average of keeper samples over the window before expiry
+}
+
+impl SettlementMode {
+    /// TWAP window a settlement price record stores for this mode, 0 for spot
+    pub fn window_secs(&self) -> u32 {
+        match self {
+            SettlementMode::Spot => 0,
+            SettlementMode::Twap { window_secs } => *window_secs,
+        }
+    }
+}
 
 #[account]
 pub struct AssetConfig {
@@ -6,11 +26,18 @@ pub struct AssetConfig {
     pub quote_mint: Pubkey,           // Always USDC
     pub pyth_feed_id: [u8; 32],      // Pyth price feed ID
     pub enabled: bool,
-    pub min_strike_percentage: u16,   // e.g., 80 = 80% of current price
-    pub max_strike_percentage: u16,   // e.g., 120 = 120% of current price
+    pub pause_flags: u8,              // PAUSE_* bits paused for this asset only
+    pub min_strike_percentage: u16,   // Basis points of spot, e.g., 8000 = 80% of current price
+    pub max_strike_percentage: u16,   // Basis points of spot, e.g., 12000 = 120% of current price
     pub min_expiry_seconds: i64,      // e.g., 1 day = 86400
     pub max_expiry_seconds: i64,      // e.g., 90 days = 7776000
     pub decimals: u8,                 // Asset decimals
+    pub quote_decimals: u8,           // Quote mint decimals
+    pub max_confidence_bps: u16,      // Max Pyth conf / price, in basis points
+    pub settlement_window_seconds: i64, // Max age of the settlement print at expiry
+    pub settlement_mode: SettlementMode,
+    pub open_quotes: u32,             // Quotes not yet closed; quote mint is frozen while > 0
+    pub open_positions: u64,          // Unsettled positions; quote mint is frozen while > 0
     pub bump: u8,
 }
 
@@ -20,10 +47,276 @@ impl AssetConfig {
         32 + // quote_mint
         32 + // pyth_feed_id
         1 +  // enabled
+        1 +  // pause_flags
         2 +  // min_strike_percentage
         2 +  // max_strike_percentage
         8 +  // min_expiry_seconds
         8 +  // max_expiry_seconds
         1 +  // decimals
+        1 +  // quote_decimals
+        2 +  // max_confidence_bps
+        8 +  // settlement_window_seconds
+        1 + 4 + // settlement_mode (tag + Twap window_secs)
+        4 +  // open_quotes
+        8 +  // open_positions
         1;   // bump
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+
+    /// Whether quotes or positions still denominated in the quote mint exist
+    pub fn has_open_exposure(&self) -> bool {
+        self.open_quotes > 0 || self.open_positions > 0
+    }
+
+    pub fn record_quote_opened(&mut self) -> Result<()> {
+        self.open_quotes = self
+            .open_quotes
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_quote_closed(&mut self) -> Result<()> {
+        self.open_quotes = self
+            .open_quotes
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_position_opened(&mut self) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_position_settled(&mut self) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Rejects strikes outside the configured band around `spot_price`
+    pub fn validate_strikes(&self, strikes: &[StrikeQuote], spot_price: u64) -> Result<()> {
+        let min_strike = math::mul_div(
+            spot_price,
+            self.min_strike_percentage as u64,
+            BASIS_POINTS_DIVISOR as u128,
+            Rounding::Up,
+        )?;
+        let max_strike = math::mul_div(
+            spot_price,
+            self.max_strike_percentage as u64,
+            BASIS_POINTS_DIVISOR as u128,
+            Rounding::Down,
+        )?;
+
+        for strike in strikes {
+            require!(
+                strike.strike_price >= min_strike && strike.strike_price <= max_strike,
+                ErrorCode::StrikeOutsideBand
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Rejects expiries outside the configured window from `now`
+    pub fn validate_expiry(&self, expiry_timestamp: i64, now: i64) -> Result<()> {
+        let time_to_expiry = expiry_timestamp
+            .checked_sub(now)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        require!(
+            time_to_expiry >= self.min_expiry_seconds && time_to_expiry <= self.max_expiry_seconds,
+            ErrorCode::ExpiryOutsideWindow
+        );
+
+        Ok(())
+    }
+
+    /// Checks config invariants; run after every mutation
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.asset_mint != self.quote_mint,
+            ErrorCode::InvalidQuoteMint
+        );
+
+        require!(
+            self.min_strike_percentage > 0
+                && self.min_strike_percentage < self.max_strike_percentage,
+            ErrorCode::InvalidStrikeRange
+        );
+
+        require!(
+            self.min_expiry_seconds > 0 && self.min_expiry_seconds < self.max_expiry_seconds,
+            ErrorCode::InvalidExpiryRange
+        );
+
+        require!(
+            self.max_confidence_bps > 0
+                && self.max_confidence_bps as u64 <= BASIS_POINTS_DIVISOR,
+            ErrorCode::InvalidConfidenceBound
+        );
+
+        require!(
+            self.settlement_window_seconds > 0,
+            ErrorCode::InvalidSettlementWindow
+        );
+
+        // Every sample slot must span at least a second
+        if let SettlementMode::Twap { window_secs } = self.settlement_mode {
+            require!(
+                window_secs >= TWAP_SAMPLE_BUCKETS,
+                ErrorCode::InvalidSettlementWindow
+            );
+        }
+
+        require!(
+            self.pause_flags & !PAUSE_ALL == 0,
+            ErrorCode::InvalidPauseFlags
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AssetUpdate;
+
+    fn config() -> AssetConfig {
+        AssetConfig {
+            asset_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            pyth_feed_id: [1; 32],
+            enabled: true,
+            pause_flags: 0,
+            min_strike_percentage: 8000,
+            max_strike_percentage: 12000,
+            min_expiry_seconds: 86400,
+            max_expiry_seconds: 7776000,
+            decimals: 9,
+            quote_decimals: 6,
+            max_confidence_bps: 100,
+            settlement_window_seconds: 60,
+            settlement_mode: SettlementMode::Spot,
+            open_quotes: 0,
+            open_positions: 0,
+            bump: 0,
+        }
+    }
+
+    fn assert_invalid(config: AssetConfig, error: ErrorCode) {
+        assert_eq!(config.validate().unwrap_err(), error.into());
+    }
+
+    #[test]
+    fn valid_config_passes() {
+        config().validate().unwrap();
+
+        let mut twap = config();
+        twap.settlement_mode = SettlementMode::Twap { window_secs: TWAP_SAMPLE_BUCKETS };
+        twap.validate().unwrap();
+    }
+
+    #[test]
+    fn quote_mint_must_differ_from_asset() {
+        let mut c = config();
+        c.quote_mint = c.asset_mint;
+        assert_invalid(c, ErrorCode::InvalidQuoteMint);
+    }
+
+    #[test]
+    fn strike_band_must_be_ordered_and_non_zero() {
+        let mut c = config();
+        c.min_strike_percentage = 0;
+        assert_invalid(c, ErrorCode::InvalidStrikeRange);
+
+        let mut c = config();
+        c.min_strike_percentage = c.max_strike_percentage;
+        assert_invalid(c, ErrorCode::InvalidStrikeRange);
+    }
+
+    #[test]
+    fn expiry_range_must_be_ordered_and_positive() {
+        let mut c = config();
+        c.min_expiry_seconds = 0;
+        assert_invalid(c, ErrorCode::InvalidExpiryRange);
+
+        let mut c = config();
+        c.max_expiry_seconds = c.min_expiry_seconds;
+        assert_invalid(c, ErrorCode::InvalidExpiryRange);
+    }
+
+    #[test]
+    fn confidence_bound_within_basis_points() {
+        let mut c = config();
+        c.max_confidence_bps = 0;
+        assert_invalid(c, ErrorCode::InvalidConfidenceBound);
+
+        let mut c = config();
+        c.max_confidence_bps = BASIS_POINTS_DIVISOR as u16 + 1;
+        assert_invalid(c, ErrorCode::InvalidConfidenceBound);
+
+        let mut c = config();
+        c.max_confidence_bps = BASIS_POINTS_DIVISOR as u16;
+        c.validate().unwrap();
+    }
+
+    #[test]
+    fn settlement_windows_must_be_usable() {
+        let mut c = config();
+        c.settlement_window_seconds = 0;
+        assert_invalid(c, ErrorCode::InvalidSettlementWindow);
+
+        let mut c = config();
+        c.settlement_mode = SettlementMode::Twap { window_secs: TWAP_SAMPLE_BUCKETS - 1 };
+        assert_invalid(c, ErrorCode::InvalidSettlementWindow);
+    }
+
+    #[test]
+    fn unknown_pause_bits_rejected() {
+        let mut c = config();
+        c.pause_flags = 1 << 7;
+        assert_invalid(c, ErrorCode::InvalidPauseFlags);
+    }
+
+    #[test]
+    fn quote_mint_frozen_with_open_exposure() {
+        let new_mint = Pubkey::new_unique();
+        let update = AssetUpdate {
+            quote_mint: Some(new_mint),
+            ..Default::default()
+        };
+
+        let mut c = config();
+        c.open_positions = 1;
+        assert_eq!(
+            update.apply(&mut c, Some((new_mint, 6))).unwrap_err(),
+            ErrorCode::AssetHasOpenExposure.into()
+        );
+
+        // Re-asserting the current mint is not a change
+        let mut c = config();
+        c.open_quotes = 1;
+        let current_mint = c.quote_mint;
+        let same_mint = AssetUpdate {
+            quote_mint: Some(current_mint),
+            ..Default::default()
+        };
+        same_mint.apply(&mut c, Some((current_mint, 6))).unwrap();
+
+        let mut c = config();
+        update.apply(&mut c, Some((new_mint, 9))).unwrap();
+        assert_eq!(c.quote_mint, new_mint);
+        assert_eq!(c.quote_decimals, 9);
+    }
 }
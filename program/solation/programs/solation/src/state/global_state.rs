@@ -1,23 +1,80 @@
 use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
 
 #[account]
 pub struct GlobalState {
     pub authority: Pubkey,        // Program admin
+    pub pending_authority: Option<Pubkey>, // Proposed new admin awaiting acceptance
     pub treasury: Pubkey,          // Fee recipient
     pub protocol_fee_bps: u16,     // Protocol fee in basis points (0 for MVP)
-    pub paused: bool,              // Emergency pause flag
+    pub max_protocol_fee_bps: u16, // Ceiling for protocol_fee_bps, at most MAX_PROTOCOL_FEE_BPS
+    pub max_price_age_seconds: u64, // Max age of a Pyth price used for quoting
+    pub timelock_delay_seconds: i64, // Delay before a queued parameter change can execute
+    pub pause_flags: u8,           // PAUSE_* bits for paused instruction classes
     pub total_volume: u64,         // Total volume traded
     pub total_positions: u64,      // Total positions created
+    pub total_fees_collected: u64, // Protocol fees collected (premium mint units)
     pub bump: u8,
 }
 
 impl GlobalState {
     pub const LEN: usize = 8 + // discriminator
         32 + // authority
+        1 + 32 + // pending_authority
         32 + // treasury
         2 +  // protocol_fee_bps
-        1 +  // paused
+        2 +  // max_protocol_fee_bps
+        8 +  // max_price_age_seconds
+        8 +  // timelock_delay_seconds
+        1 +  // pause_flags
         8 +  // total_volume
         8 +  // total_positions
+        8 +  // total_fees_collected
         1;   // bump
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+
+    /// Checks config invariants; run after every mutation
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS
+                && self.protocol_fee_bps <= self.max_protocol_fee_bps,
+            ErrorCode::ProtocolFeeTooHigh
+        );
+
+        require!(
+            self.treasury != Pubkey::default(),
+            ErrorCode::InvalidTreasury
+        );
+
+        require!(
+            self.max_price_age_seconds > 0,
+            ErrorCode::InvalidPriceAge
+        );
+
+        require!(
+            (0..=MAX_TIMELOCK_DELAY_SECONDS).contains(&self.timelock_delay_seconds),
+            ErrorCode::InvalidTimelockDelay
+        );
+
+        require!(
+            self.pause_flags & !PAUSE_ALL == 0,
+            ErrorCode::InvalidPauseFlags
+        );
+
+        Ok(())
+    }
+}
+
+// Deployment configuration passed to initialize_global_state
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GlobalStateConfig {
+    pub treasury: Pubkey,           // Owner of the treasury token accounts
+    pub protocol_fee_bps: u16,
+    pub max_protocol_fee_bps: u16,
+    pub max_price_age_seconds: u64,
+    pub timelock_delay_seconds: i64,
 }
@@ -3,19 +3,25 @@ use anchor_lang::prelude::*;
 #[account]
 pub struct MarketMaker {
     pub owner: Pubkey,               // MM wallet address
+    pub original_owner: Pubkey,      // Registering wallet, PDA seed (never changes)
+    pub pending_owner: Option<Pubkey>, // Proposed new owner awaiting acceptance
     pub active: bool,                // Can accept new positions
     pub total_positions: u64,        // Total positions count
     pub completed_positions: u64,    // Settled positions count
     pub reputation_score: u16,       // Future: reputation system
+    pub next_quote_id: u64,          // Id assigned to the next submitted quote (PDA seed)
     pub bump: u8,
 }
 
 impl MarketMaker {
     pub const LEN: usize = 8 + // discriminator
         32 + // owner
+        32 + // original_owner
+        1 + 32 + // pending_owner
         1 +  // active
         8 +  // total_positions
         8 +  // completed_positions
         2 +  // reputation_score
+        8 +  // next_quote_id
         1;   // bump
 }
@@ -1,15 +1,23 @@
 pub mod asset_config;
 pub mod global_state;
 pub mod market_maker;
+pub mod pending_change;
 pub mod position;
 pub mod position_request;
 pub mod quote;
+pub mod role_registry;
+pub mod settlement_price;
+pub mod user_account;
 pub mod vault;
 
 pub use asset_config::*;
 pub use global_state::*;
 pub use market_maker::*;
+pub use pending_change::*;
 pub use position::*;
 pub use position_request::*;
 pub use quote::*;
+pub use role_registry::*;
+pub use settlement_price::*;
+pub use user_account::*;
 pub use vault::*;
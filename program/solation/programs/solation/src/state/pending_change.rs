@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::{AssetConfig, GlobalState, SettlementMode};
+
+// Risk parameter update for an asset; `None` leaves the field unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct AssetUpdate {
+    pub enabled: Option<bool>,
+    pub pyth_feed_id: Option<[u8; 32]>,
+    pub quote_mint: Option<Pubkey>,  // New quote decimals are read from the mint
+    pub min_strike_percentage: Option<u16>,
+    pub max_strike_percentage: Option<u16>,
+    pub min_expiry_seconds: Option<i64>,
+    pub max_expiry_seconds: Option<i64>,
+    pub max_confidence_bps: Option<u16>,
+    pub settlement_window_seconds: Option<i64>,
+    pub settlement_mode: Option<SettlementMode>,
+}
+
+impl AssetUpdate {
+    pub const LEN: usize = 1 + 1 + // enabled
+        1 + 32 + // pyth_feed_id
+        1 + 32 + // quote_mint
+        1 + 2 + // min_strike_percentage
+        1 + 2 + // max_strike_percentage
+        1 + 8 + // min_expiry_seconds
+        1 + 8 + // max_expiry_seconds
+        1 + 2 + // max_confidence_bps
+        1 + 8 + // settlement_window_seconds
+        1 + 1 + 4; // settlement_mode
+
+    /// Applies the update and re-validates the config. `quote_mint` is the
+    /// (key, decimals) of the new quote mint account when the mint changes.
+    pub fn apply(
+        &self,
+        asset_config: &mut AssetConfig,
+        quote_mint: Option<(Pubkey, u8)>,
+    ) -> Result<()> {
+        if let Some(e) = self.enabled {
+            asset_config.enabled = e;
+        }
+
+        if let Some(feed_id) = self.pyth_feed_id {
+            asset_config.pyth_feed_id = feed_id;
+        }
+
+        // Quote decimals always come from the mint account itself. Strikes,
+        // vaults and settlement prices of open quotes and positions are in the
+        // old mint, so it can only change once none are left.
+        if let Some(new_quote_mint) = self.quote_mint {
+            let (key, decimals) = quote_mint.ok_or(ErrorCode::InvalidQuoteMint)?;
+            require_keys_eq!(key, new_quote_mint, ErrorCode::InvalidQuoteMint);
+            require!(
+                new_quote_mint == asset_config.quote_mint || !asset_config.has_open_exposure(),
+                ErrorCode::AssetHasOpenExposure
+            );
+            asset_config.quote_mint = new_quote_mint;
+            asset_config.quote_decimals = decimals;
+        }
+
+        if let Some(min) = self.min_strike_percentage {
+            asset_config.min_strike_percentage = min;
+        }
+
+        if let Some(max) = self.max_strike_percentage {
+            asset_config.max_strike_percentage = max;
+        }
+
+        if let Some(min) = self.min_expiry_seconds {
+            asset_config.min_expiry_seconds = min;
+        }
+
+        if let Some(max) = self.max_expiry_seconds {
+            asset_config.max_expiry_seconds = max;
+        }
+
+        if let Some(bps) = self.max_confidence_bps {
+            asset_config.max_confidence_bps = bps;
+        }
+
+        if let Some(window) = self.settlement_window_seconds {
+            asset_config.settlement_window_seconds = window;
+        }
+
+        if let Some(mode) = self.settlement_mode {
+            asset_config.settlement_mode = mode;
+        }
+
+        asset_config.validate()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ParameterChange {
+    ProtocolFee { protocol_fee_bps: u16 },       // Targets GlobalState
+    TimelockDelay { delay_seconds: i64 },        // Targets GlobalState
+    Asset(AssetUpdate),                          // Targets an AssetConfig
+    Treasury { treasury: Pubkey },               // Targets GlobalState
+    MaxProtocolFee { max_protocol_fee_bps: u16 }, // Targets GlobalState
+    MaxPriceAge { max_price_age_seconds: u64 },  // Targets GlobalState
+}
+
+impl ParameterChange {
+    pub const LEN: usize = 1 + AssetUpdate::LEN; // tag + largest variant
+
+    /// Applies a GlobalState change and re-validates it; asset updates are
+    /// applied through `AssetUpdate::apply` instead
+    pub fn apply_to_global(&self, global_state: &mut GlobalState) -> Result<()> {
+        match self {
+            ParameterChange::ProtocolFee { protocol_fee_bps } => {
+                global_state.protocol_fee_bps = *protocol_fee_bps;
+            }
+            ParameterChange::TimelockDelay { delay_seconds } => {
+                global_state.timelock_delay_seconds = *delay_seconds;
+            }
+            ParameterChange::Treasury { treasury } => {
+                global_state.treasury = *treasury;
+            }
+            ParameterChange::MaxProtocolFee { max_protocol_fee_bps } => {
+                global_state.max_protocol_fee_bps = *max_protocol_fee_bps;
+            }
+            ParameterChange::MaxPriceAge { max_price_age_seconds } => {
+                global_state.max_price_age_seconds = *max_price_age_seconds;
+            }
+            ParameterChange::Asset(_) => return err!(ErrorCode::InvalidChangeTarget),
+        }
+
+        global_state.validate()
+    }
+}
+
+// Queued parameter change, one per target account, executable after `eta`
+#[account]
+pub struct PendingChange {
+    pub target: Pubkey,               // GlobalState or AssetConfig being changed
+    pub change: ParameterChange,
+    pub proposer: Pubkey,             // Queued by (receives rent back)
+    pub queued_at: i64,
+    pub eta: i64,                     // Earliest execution time
+    pub bump: u8,
+}
+
+impl PendingChange {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // target
+        ParameterChange::LEN + // change
+        32 + // proposer
+        8 +  // queued_at
+        8 +  // eta
+        1;   // bump
+
+    pub fn is_ready(&self, now: i64) -> bool {
+        now >= self.eta
+    }
+}
@@ -11,15 +11,20 @@ pub enum PositionStatus {
 
 #[account]
 pub struct Position {
-    pub position_id: u64,             // Unique position ID
+    pub position_id: u64,             // Allocated from the user's UserAccount counter
+    pub request: Pubkey,              // PositionRequest this position was opened from (closed on confirm)
+    pub request_id: u64,              // ID of that request, kept for traceability
     pub user: Pubkey,
     pub market_maker: Pubkey,
     pub strategy: StrategyType,
     pub asset_mint: Pubkey,           // Underlying asset
     pub quote_mint: Pubkey,           // USDC
     pub strike_price: u64,            // Strike price in USDC terms
-    pub premium_paid: u64,            // Premium user received upfront
+    pub premium_paid: u64,            // Premium user received upfront (net of fee)
+    pub protocol_fee: u64,            // Protocol fee skimmed from the premium
     pub contract_size: u64,           // Amount of underlying
+    pub user_collateral: u64,         // Amount locked by the user
+    pub mm_collateral: u64,           // Amount locked by the MM
     pub created_at: i64,
     pub expiry_timestamp: i64,
     pub settlement_price: Option<u64>, // Pyth price at settlement
@@ -29,6 +34,8 @@ pub struct Position {
     pub user_vault: Pubkey,           // User's locked asset PDA
     pub mm_vault_locked: Pubkey,      // MM's locked asset PDA
 
+    pub rent_payer: Pubkey,           // Paid rent for the position and its vaults
+
     pub bump: u8,
     pub user_vault_bump: u8,
     pub mm_vault_bump: u8,
@@ -37,6 +44,8 @@ pub struct Position {
 impl Position {
     pub const LEN: usize = 8 + // discriminator
         8 +  // position_id
+        32 + // request
+        8 +  // request_id
         32 + // user
         32 + // market_maker
         1 +  // strategy
@@ -44,14 +53,42 @@ impl Position {
         32 + // quote_mint
         8 +  // strike_price
         8 +  // premium_paid
+        8 +  // protocol_fee
         8 +  // contract_size
+        8 +  // user_collateral
+        8 +  // mm_collateral
         8 +  // created_at
         8 +  // expiry_timestamp
         1 + 8 + // settlement_price (Option<u64>)
         1 +  // status
         32 + // user_vault
         32 + // mm_vault_locked
+        32 + // rent_payer
         1 +  // bump
         1 +  // user_vault_bump
         1;   // mm_vault_bump
+
+    pub fn is_settled(&self) -> bool {
+        self.status != PositionStatus::Active
+    }
+
+    /// Owners entitled to each vault's collateral once settled, as (user vault,
+    /// MM vault): ITM swaps the two sides, otherwise each side gets its own back
+    pub fn vault_recipients(&self, mm_owner: Pubkey) -> (Pubkey, Pubkey) {
+        if self.status == PositionStatus::SettledITM {
+            (mm_owner, self.user)
+        } else {
+            (self.user, mm_owner)
+        }
+    }
+
+    /// Mint the user locked for this position (underlying for calls, quote mint for puts)
+    pub fn user_collateral_mint(&self) -> Pubkey {
+        self.strategy.user_collateral_mint(self.asset_mint, self.quote_mint)
+    }
+
+    /// Mint the MM locked for this position (quote mint for calls, underlying for puts)
+    pub fn mm_collateral_mint(&self) -> Pubkey {
+        self.strategy.mm_collateral_mint(self.asset_mint, self.quote_mint)
+    }
 }
@@ -1,14 +1,7 @@
 use anchor_lang::prelude::*;
 use super::StrategyType;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum RequestStatus {
-    Pending,
-    Accepted,
-    Rejected,
-    Expired,
-}
-
+// Requests only exist while pending: confirm, reject and cancel all close the account
 #[account]
 pub struct PositionRequest {
     pub request_id: u64,              // Unique request ID per user
@@ -21,10 +14,11 @@ pub struct PositionRequest {
     pub strike_price: u64,            // Chosen strike price
     pub contract_size: u64,           // Requested contract size
     pub premium: u64,                 // Calculated premium (strike.premium_per_contract * contract_size)
+    pub escrow_amount: u64,           // User collateral held in the request escrow
     pub created_at: i64,              // When request was made
     pub expires_at: i64,              // created_at + MM_CONFIRMATION_WINDOW
-    pub status: RequestStatus,        // Current status
     pub bump: u8,
+    pub escrow_bump: u8,
 }
 
 impl PositionRequest {
@@ -39,16 +33,23 @@ impl PositionRequest {
         8 +   // strike_price
         8 +   // contract_size
         8 +   // premium
+        8 +   // escrow_amount
         8 +   // created_at
         8 +   // expires_at
-        1 +   // status
-        1;    // bump
+        1 +   // bump
+        1;    // escrow_bump
 
     pub fn is_expired(&self, current_timestamp: i64) -> bool {
         current_timestamp >= self.expires_at
     }
 
-    pub fn is_pending(&self) -> bool {
-        self.status == RequestStatus::Pending
+    /// Mint the user escrowed for this request (underlying for calls, quote mint for puts)
+    pub fn user_collateral_mint(&self) -> Pubkey {
+        self.strategy.user_collateral_mint(self.asset_mint, self.quote_mint)
+    }
+
+    /// Mint the MM locks on confirmation (quote mint for calls, underlying for puts)
+    pub fn mm_collateral_mint(&self) -> Pubkey {
+        self.strategy.mm_collateral_mint(self.asset_mint, self.quote_mint)
     }
 }
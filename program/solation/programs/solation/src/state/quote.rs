@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum StrategyType {
@@ -6,25 +7,54 @@ pub enum StrategyType {
     CashSecuredPut,
 }
 
+impl StrategyType {
+    /// Mint the user locks (underlying for calls, quote mint for puts)
+    pub fn user_collateral_mint(&self, asset_mint: Pubkey, quote_mint: Pubkey) -> Pubkey {
+        match self {
+            StrategyType::CoveredCall => asset_mint,
+            StrategyType::CashSecuredPut => quote_mint,
+        }
+    }
+
+    /// Mint the MM locks (quote mint for calls, underlying for puts)
+    pub fn mm_collateral_mint(&self, asset_mint: Pubkey, quote_mint: Pubkey) -> Pubkey {
+        match self {
+            StrategyType::CoveredCall => quote_mint,
+            StrategyType::CashSecuredPut => asset_mint,
+        }
+    }
+
+    /// Whether the option finishes in the money (calls above the strike, puts below)
+    pub fn is_in_the_money(&self, strike_price: u64, settlement_price: u64) -> bool {
+        match self {
+            StrategyType::CoveredCall => settlement_price > strike_price,
+            StrategyType::CashSecuredPut => settlement_price < strike_price,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct StrikeQuote {
-    pub strike_price: u64,           // In quote decimals (USDC, 6 decimals)
-    pub premium_per_contract: u64,   // Premium quoted by MM
+    pub strike_price: u64,           // Quote mint units per whole asset unit
+    pub premium_per_contract: u64,   // Premium quoted by MM, quote mint units per whole asset unit
     pub available_contracts: u64,    // How many can be sold
 }
 
 #[account]
 pub struct Quote {
     pub market_maker: Pubkey,
+    pub quote_id: u64,                // Per-MM id, PDA seed
     pub asset_mint: Pubkey,           // Underlying asset
     pub quote_mint: Pubkey,           // Always USDC
     pub strategy: StrategyType,       // CoveredCall or CashSecuredPut
     pub strikes: Vec<StrikeQuote>,    // Up to 10 strike prices
-    pub expiry_timestamp: i64,        // When this quote expires
+    pub valid_until: i64,             // Quote is fillable until this time (good-till)
+    pub option_expiry: i64,           // Expiry of positions opened from this quote (immutable)
     pub min_size: u64,                // Minimum contract size
     pub max_size: u64,                // Maximum per user
     pub last_updated: i64,
     pub active: bool,
+    pub pending_requests: u32,        // Open PositionRequests; quote can't close while > 0
     pub bump: u8,
 }
 
@@ -34,14 +64,70 @@ impl Quote {
 
     pub const LEN: usize = 8 + // discriminator
         32 + // market_maker
+        8 +  // quote_id
         32 + // asset_mint
         32 + // quote_mint
         1 +  // strategy
         4 + (Self::MAX_STRIKES * (8 + 8 + 8)) + // strikes vec (price, premium, available)
-        8 +  // expiry_timestamp
+        8 +  // valid_until
+        8 +  // option_expiry
         8 +  // min_size
         8 +  // max_size
         8 +  // last_updated
         1 +  // active
+        4 +  // pending_requests
         1;   // bump
+
+    /// Mint the user escrows for this strategy (underlying for calls, quote mint for puts)
+    pub fn user_collateral_mint(&self) -> Pubkey {
+        self.strategy.user_collateral_mint(self.asset_mint, self.quote_mint)
+    }
+
+    /// Requires a non-zero minimum size no larger than the maximum
+    pub fn validate_sizes(&self) -> Result<()> {
+        require!(
+            self.min_size > 0 && self.max_size >= self.min_size,
+            ErrorCode::InvalidQuoteParameters
+        );
+        Ok(())
+    }
+
+    /// Past its good-till time, so it can no longer be filled
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.valid_until
+    }
+
+    /// Tracks a new request against this quote
+    pub fn open_request(&mut self) -> Result<()> {
+        self.pending_requests = self
+            .pending_requests
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Releases a request once it is confirmed, rejected or cancelled
+    pub fn close_request(&mut self) -> Result<()> {
+        self.pending_requests = self
+            .pending_requests
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Consumes `contract_size` contracts from the capacity advertised for `strike_price`
+    pub fn fill_strike(&mut self, strike_price: u64, contract_size: u64) -> Result<()> {
+        let strike = self
+            .strikes
+            .iter_mut()
+            .find(|s| s.strike_price == strike_price)
+            .ok_or(ErrorCode::StrikePriceNotFound)?;
+
+        strike.available_contracts = strike
+            .available_contracts
+            .checked_sub(contract_size)
+            .ok_or(ErrorCode::QuoteCapacityExhausted)?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,       // Can pause and unpause the protocol and disable assets
+    AssetManager, // Can add and update asset configs
+    FeeManager,   // Can change the protocol fee
+    Keeper,       // Submits TWAP samples and publishes settlement prices
+}
+
+// Admin roles assigned by the super-admin (GlobalState.authority)
+#[account]
+pub struct RoleRegistry {
+    pub pauser: Pubkey,
+    pub asset_manager: Pubkey,
+    pub fee_manager: Pubkey,
+    pub keeper: Pubkey,
+    pub bump: u8,
+}
+
+impl RoleRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pauser
+        32 + // asset_manager
+        32 + // fee_manager
+        32 + // keeper
+        1;   // bump
+
+    pub fn holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Pauser => self.pauser,
+            Role::AssetManager => self.asset_manager,
+            Role::FeeManager => self.fee_manager,
+            Role::Keeper => self.keeper,
+        }
+    }
+
+    pub fn set_holder(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::Pauser => self.pauser = holder,
+            Role::AssetManager => self.asset_manager = holder,
+            Role::FeeManager => self.fee_manager = holder,
+            Role::Keeper => self.keeper = holder,
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::state::SettlementMode;
+
+#[account]
+pub struct SettlementPriceRecord {
+    pub asset_mint: Pubkey,
+    pub expiry_timestamp: i64,        // Expiry this record fixes a price for
+    pub window_secs: u32,             // TWAP window at creation, 0 for spot settlement
+    pub sample_count: u32,            // Keeper samples accumulated so far
+    pub price_sum: u128,              // Sum of sampled prices (quote mint decimals)
+    pub filled_buckets: u32,          // Bit per TWAP_SAMPLE_BUCKETS slot already sampled
+    pub settlement_price: Option<u64>, // Fixed expiry price, immutable once set
+    pub published_at: i64,            // When the expiry price was fixed
+    pub bump: u8,
+}
+
+impl SettlementPriceRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // asset_mint
+        8 +  // expiry_timestamp
+        4 +  // window_secs
+        4 +  // sample_count
+        16 + // price_sum
+        4 +  // filled_buckets
+        1 + 8 + // settlement_price (Option<u64>)
+        8 +  // published_at
+        1;   // bump
+
+    pub fn is_published(&self) -> bool {
+        self.settlement_price.is_some()
+    }
+
+    /// How this expiry settles, fixed from the asset's mode when the record was
+    /// made so a later mode change cannot strand its samples
+    pub fn settlement_mode(&self) -> SettlementMode {
+        match self.window_secs {
+            0 => SettlementMode::Spot,
+            window_secs => SettlementMode::Twap { window_secs },
+        }
+    }
+
+    /// Whether the super-admin fallback may publish for this expiry
+    pub fn fallback_open(&self, now: i64) -> Result<bool> {
+        let opens_at = self
+            .expiry_timestamp
+            .checked_add(SETTLEMENT_FALLBACK_DELAY_SECONDS)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(now >= opens_at)
+    }
+
+    /// Adds a price sample published within the TWAP window before expiry. The
+    /// window is split into TWAP_SAMPLE_BUCKETS equal slots holding one sample
+    /// each, so the average has to cover the window rather than a burst of prints.
+    pub fn add_sample(&mut self, price: u64, publish_time: i64) -> Result<()> {
+        require!(
+            !self.is_published(),
+            ErrorCode::SettlementPriceAlreadyPublished
+        );
+        require!(
+            self.settlement_mode() != SettlementMode::Spot,
+            ErrorCode::NotTwapSettlement
+        );
+
+        let bucket = self.bucket(publish_time)?;
+        require!(
+            self.filled_buckets & (1 << bucket) == 0,
+            ErrorCode::PriceBucketAlreadySampled
+        );
+
+        self.price_sum = self
+            .price_sum
+            .checked_add(price as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.sample_count = self
+            .sample_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.filled_buckets |= 1 << bucket;
+
+        Ok(())
+    }
+
+    /// Slot of the TWAP window `publish_time` falls in; a print at expiry
+    /// belongs to the last slot
+    fn bucket(&self, publish_time: i64) -> Result<u32> {
+        let window_start = self
+            .expiry_timestamp
+            .checked_sub(self.window_secs as i64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            publish_time >= window_start && publish_time <= self.expiry_timestamp,
+            ErrorCode::PriceOutsideSettlementWindow
+        );
+
+        let offset = (publish_time - window_start) as u64;
+        let bucket = offset * TWAP_SAMPLE_BUCKETS as u64 / self.window_secs as u64;
+        Ok((bucket as u32).min(TWAP_SAMPLE_BUCKETS - 1))
+    }
+
+    /// Fixes the expiry price; fails if it has already been published
+    pub fn publish(&mut self, price: u64, published_at: i64) -> Result<()> {
+        require!(
+            !self.is_published(),
+            ErrorCode::SettlementPriceAlreadyPublished
+        );
+
+        self.settlement_price = Some(price);
+        self.published_at = published_at;
+
+        Ok(())
+    }
+
+    /// Average of the accumulated samples, rounded down, once enough of the
+    /// window's slots have been sampled
+    pub fn twap(&self) -> Result<u64> {
+        require!(
+            self.sample_count >= MIN_TWAP_SAMPLES,
+            ErrorCode::InsufficientPriceSamples
+        );
+
+        u64::try_from(self.price_sum / self.sample_count as u128)
+            .map_err(|_| ErrorCode::MathOverflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPIRY: i64 = 1_000_000;
+    const WINDOW: u32 = 1_200; // 100 second slots
+
+    fn record(window_secs: u32) -> SettlementPriceRecord {
+        SettlementPriceRecord {
+            asset_mint: Pubkey::default(),
+            expiry_timestamp: EXPIRY,
+            window_secs,
+            sample_count: 0,
+            price_sum: 0,
+            filled_buckets: 0,
+            settlement_price: None,
+            published_at: 0,
+            bump: 0,
+        }
+    }
+
+    fn slot_time(slot: i64) -> i64 {
+        EXPIRY - WINDOW as i64 + slot * 100
+    }
+
+    #[test]
+    fn twap_averages_one_sample_per_slot() {
+        let mut record = record(WINDOW);
+        for slot in 0..8 {
+            record.add_sample(100, slot_time(slot)).unwrap();
+        }
+        record.add_sample(101, slot_time(8)).unwrap();
+
+        assert_eq!(record.sample_count, MIN_TWAP_SAMPLES);
+        // 901 / 9, rounded down
+        assert_eq!(record.twap().unwrap(), 100);
+    }
+
+    #[test]
+    fn twap_needs_minimum_coverage() {
+        let mut record = record(WINDOW);
+        for slot in 0..(MIN_TWAP_SAMPLES as i64 - 1) {
+            record.add_sample(100, slot_time(slot)).unwrap();
+        }
+
+        assert_eq!(
+            record.twap().unwrap_err(),
+            ErrorCode::InsufficientPriceSamples.into()
+        );
+    }
+
+    #[test]
+    fn burst_before_expiry_fills_one_slot() {
+        let mut record = record(WINDOW);
+        record.add_sample(100, EXPIRY - 3).unwrap();
+
+        assert_eq!(
+            record.add_sample(100, EXPIRY - 2).unwrap_err(),
+            ErrorCode::PriceBucketAlreadySampled.into()
+        );
+        assert_eq!(
+            record.add_sample(100, EXPIRY).unwrap_err(),
+            ErrorCode::PriceBucketAlreadySampled.into()
+        );
+    }
+
+    #[test]
+    fn print_at_expiry_does_not_block_earlier_slots() {
+        let mut record = record(WINDOW);
+        record.add_sample(100, EXPIRY).unwrap();
+        record.add_sample(100, slot_time(0)).unwrap();
+
+        assert_eq!(record.sample_count, 2);
+    }
+
+    #[test]
+    fn samples_outside_window_rejected() {
+        let mut record = record(WINDOW);
+
+        assert_eq!(
+            record.add_sample(100, slot_time(0) - 1).unwrap_err(),
+            ErrorCode::PriceOutsideSettlementWindow.into()
+        );
+        assert_eq!(
+            record.add_sample(100, EXPIRY + 1).unwrap_err(),
+            ErrorCode::PriceOutsideSettlementWindow.into()
+        );
+    }
+
+    #[test]
+    fn spot_record_takes_no_samples() {
+        let mut record = record(0);
+
+        assert_eq!(
+            record.add_sample(100, EXPIRY).unwrap_err(),
+            ErrorCode::NotTwapSettlement.into()
+        );
+    }
+
+    #[test]
+    fn mode_round_trips_through_window() {
+        let twap = SettlementMode::Twap { window_secs: WINDOW };
+
+        assert_eq!(record(twap.window_secs()).settlement_mode(), twap);
+        assert_eq!(
+            record(SettlementMode::Spot.window_secs()).settlement_mode(),
+            SettlementMode::Spot
+        );
+    }
+
+    #[test]
+    fn mode_switch_after_initialization_keeps_record_mode() {
+        // Made under a TWAP, then the asset moves to spot before publishing
+        let mut asset_mode = SettlementMode::Twap { window_secs: WINDOW };
+        let mut twap_record = record(asset_mode.window_secs());
+        asset_mode = SettlementMode::Spot;
+        for slot in 0..MIN_TWAP_SAMPLES as i64 {
+            twap_record.add_sample(100, slot_time(slot)).unwrap();
+        }
+
+        assert_ne!(twap_record.settlement_mode(), asset_mode);
+        assert_eq!(twap_record.twap().unwrap(), 100);
+
+        // Made under spot, then the asset moves to a TWAP
+        let mut spot_record = record(asset_mode.window_secs());
+        asset_mode = SettlementMode::Twap { window_secs: WINDOW };
+
+        assert_ne!(spot_record.settlement_mode(), asset_mode);
+        assert_eq!(
+            spot_record.add_sample(100, EXPIRY).unwrap_err(),
+            ErrorCode::NotTwapSettlement.into()
+        );
+    }
+
+    #[test]
+    fn published_record_is_final() {
+        let mut record = record(WINDOW);
+        record.publish(100, EXPIRY + 10).unwrap();
+
+        assert_eq!(
+            record.publish(101, EXPIRY + 20).unwrap_err(),
+            ErrorCode::SettlementPriceAlreadyPublished.into()
+        );
+        assert_eq!(
+            record.add_sample(100, EXPIRY).unwrap_err(),
+            ErrorCode::SettlementPriceAlreadyPublished.into()
+        );
+        assert_eq!(record.settlement_price, Some(100));
+    }
+
+    #[test]
+    fn fallback_opens_after_delay() {
+        let record = record(WINDOW);
+
+        assert!(!record
+            .fallback_open(EXPIRY + SETTLEMENT_FALLBACK_DELAY_SECONDS - 1)
+            .unwrap());
+        assert!(record
+            .fallback_open(EXPIRY + SETTLEMENT_FALLBACK_DELAY_SECONDS)
+            .unwrap());
+    }
+}
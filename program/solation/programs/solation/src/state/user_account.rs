@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+// Per-user index: requests and positions are derived from
+// [POSITION_REQUEST_SEED | POSITION_SEED, user, 0..next_*_id]
+#[account]
+pub struct UserAccount {
+    pub user: Pubkey,
+    pub next_request_id: u64,         // Id assigned to the user's next request
+    pub next_position_id: u64,        // Id assigned to the user's next position
+    pub open_positions: u32,          // Confirmed positions not yet settled
+    pub lifetime_premium_earned: u64, // Net premium received (quote mint units)
+    pub notional_traded: u64,         // Strike notional of confirmed positions (quote mint units)
+    pub bump: u8,
+}
+
+impl UserAccount {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        8 +  // next_request_id
+        8 +  // next_position_id
+        4 +  // open_positions
+        8 +  // lifetime_premium_earned
+        8 +  // notional_traded
+        1;   // bump
+
+    /// Returns the next request id and advances the counter
+    pub fn allocate_request_id(&mut self) -> Result<u64> {
+        let request_id = self.next_request_id;
+        self.next_request_id = request_id
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(request_id)
+    }
+
+    /// Returns the next position id and advances the counter
+    pub fn allocate_position_id(&mut self) -> Result<u64> {
+        let position_id = self.next_position_id;
+        self.next_position_id = position_id
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(position_id)
+    }
+
+    /// Records a confirmed position in the user's totals
+    pub fn record_position_opened(&mut self, premium: u64, notional: u64) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.lifetime_premium_earned = self
+            .lifetime_premium_earned
+            .checked_add(premium)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.notional_traded = self
+            .notional_traded
+            .checked_add(notional)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_position_settled(&mut self) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
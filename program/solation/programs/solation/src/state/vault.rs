@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
 
 #[account]
 pub struct MarketMakerVault {
@@ -22,4 +23,35 @@ impl MarketMakerVault {
         8 +  // locked_liquidity
         1 +  // bump
         1;   // vault_bump
+
+    /// Moves `amount` from available to locked liquidity when it backs a position
+    pub fn lock(&mut self, amount: u64) -> Result<()> {
+        self.available_liquidity = self
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InsufficientLiquidity)?;
+        self.locked_liquidity = self
+            .locked_liquidity
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Releases `amount` of locked liquidity once the position is settled
+    pub fn unlock(&mut self, amount: u64) -> Result<()> {
+        self.locked_liquidity = self
+            .locked_liquidity
+            .checked_sub(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Removes `amount` paid out of the vault (e.g. premium) from available liquidity
+    pub fn debit(&mut self, amount: u64) -> Result<()> {
+        self.available_liquidity = self
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InsufficientLiquidity)?;
+        Ok(())
+    }
 }
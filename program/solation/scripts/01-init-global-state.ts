@@ -1,14 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
 import { Solation } from "../target/types/solation";
 import { getGlobalStatePDA } from "./helpers/pdas";
-import { PROTOCOL_FEE_BPS } from "./helpers/constants";
+import {
+  PROTOCOL_FEE_BPS,
+  MAX_PROTOCOL_FEE_BPS,
+  TIMELOCK_DELAY_SECONDS,
+  ORACLE_PARAMS,
+} from "./helpers/constants";
 
 /**
  * Script 01: Initialize Global State
  *
  * This script initializes the protocol's global state account.
- * Should be run only once per deployment.
+ * Should be run only once per deployment, by the program's upgrade authority.
  */
 async function main() {
   console.log("🚀 Initializing Global State...\n");
@@ -24,10 +30,17 @@ async function main() {
   // Derive global state PDA
   const [globalState, globalStateBump] = getGlobalStatePDA();
 
+  // ProgramData account holding the upgrade authority
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   console.log("Configuration:");
   console.log(`  Authority: ${authority.toBase58()}`);
   console.log(`  Treasury: ${treasury.toBase58()}`);
   console.log(`  Protocol Fee: ${PROTOCOL_FEE_BPS} bps (${PROTOCOL_FEE_BPS / 100}%)`);
+  console.log(`  Max Protocol Fee: ${MAX_PROTOCOL_FEE_BPS} bps`);
   console.log(`  Global State PDA: ${globalState.toBase58()}\n`);
 
   try {
@@ -45,10 +58,17 @@ async function main() {
 
     // Initialize global state
     const tx = await program.methods
-      .initializeGlobalState(PROTOCOL_FEE_BPS)
-      .accounts({
-        authority,
+      .initializeGlobalState({
         treasury,
+        protocolFeeBps: PROTOCOL_FEE_BPS,
+        maxProtocolFeeBps: MAX_PROTOCOL_FEE_BPS,
+        maxPriceAgeSeconds: new anchor.BN(ORACLE_PARAMS.MAX_PRICE_AGE_SECONDS),
+        timelockDelaySeconds: new anchor.BN(TIMELOCK_DELAY_SECONDS),
+      })
+      .accountsPartial({
+        authority,
+        program: program.programId,
+        programData,
       })
       .rpc();
 
@@ -61,7 +81,7 @@ async function main() {
     console.log(`  Authority: ${state.authority.toBase58()}`);
     console.log(`  Treasury: ${state.treasury.toBase58()}`);
     console.log(`  Fee (bps): ${state.protocolFeeBps}`);
-    console.log(`  Paused: ${state.paused}`);
+    console.log(`  Pause Flags: ${state.pauseFlags}`);
     console.log(`  Total Volume: ${state.totalVolume.toString()}`);
     console.log(`  Total Positions: ${state.totalPositions.toString()}`);
 
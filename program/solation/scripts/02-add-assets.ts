@@ -9,16 +9,18 @@ import {
   DECIMALS,
   STRIKE_PARAMS,
   EXPIRY_PARAMS,
+  ORACLE_PARAMS,
 } from "./helpers/constants";
 
 /**
  * Script 02: Add Assets
  *
- * This script adds asset configurations for SOL and USDC.
- * It also creates a USDC mint on localnet for testing.
+ * This script creates a USDC mint on localnet for testing and adds the
+ * SOL asset configuration, quoted in USDC. USDC is only a quote mint, so
+ * it has no asset config of its own.
  */
 async function main() {
-  console.log("🪙 Adding Assets (SOL & USDC)...\n");
+  console.log("🪙 Adding Assets (SOL)...\n");
 
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -59,10 +61,14 @@ async function main() {
           STRIKE_PARAMS.MAX_STRIKE_PERCENTAGE,
           new anchor.BN(EXPIRY_PARAMS.MIN_EXPIRY_SECONDS),
           new anchor.BN(EXPIRY_PARAMS.MAX_EXPIRY_SECONDS),
-          DECIMALS.SOL
+          ORACLE_PARAMS.MAX_CONFIDENCE_BPS,
+          new anchor.BN(ORACLE_PARAMS.SETTLEMENT_WINDOW_SECONDS),
+          { spot: {} } // settlement mode
         )
         .accounts({
-          authority,
+          assetMintAccount: SOL_MINT,
+          quoteMintAccount: usdcMint,
+          assetManager: authority,
         })
         .rpc();
 
@@ -76,50 +82,11 @@ async function main() {
     throw error;
   }
 
-  // Step 3: Add USDC asset config
-  console.log("Step 3: Adding USDC asset configuration...");
-  const [usdcAssetConfig] = getAssetConfigPDA(usdcMint);
-
-  try {
-    // Check if USDC config already exists
-    try {
-      const existing = await program.account.assetConfig.fetch(usdcAssetConfig);
-      console.log("⚠️  USDC asset config already exists");
-      console.log(`  Enabled: ${existing.enabled}`);
-    } catch (err) {
-      // Not initialized, add it
-      const tx = await program.methods
-        .addAsset(
-          usdcMint,
-          usdcMint, // quote mint (USDC/USDC for cash-secured puts)
-          PYTH_FEED_IDS.USDC,
-          STRIKE_PARAMS.MIN_STRIKE_PERCENTAGE,
-          STRIKE_PARAMS.MAX_STRIKE_PERCENTAGE,
-          new anchor.BN(EXPIRY_PARAMS.MIN_EXPIRY_SECONDS),
-          new anchor.BN(EXPIRY_PARAMS.MAX_EXPIRY_SECONDS),
-          DECIMALS.USDC
-        )
-        .accounts({
-          authority,
-        })
-        .rpc();
-
-      console.log(`✅ USDC asset added successfully!`);
-      console.log(`  Transaction: ${tx}`);
-      console.log(`  Asset Config: ${usdcAssetConfig.toBase58()}\n`);
-    }
-  } catch (error) {
-    console.error("❌ Error adding USDC asset:");
-    console.error(error);
-    throw error;
-  }
-
   // Summary
   console.log("📊 Summary:");
   console.log(`  SOL Mint: ${SOL_MINT.toBase58()}`);
   console.log(`  USDC Mint: ${usdcMint.toBase58()}`);
   console.log(`  SOL Asset Config: ${solAssetConfig.toBase58()}`);
-  console.log(`  USDC Asset Config: ${usdcAssetConfig.toBase58()}`);
   console.log("\n⚠️  IMPORTANT: Save the USDC mint address for use in other scripts!");
   console.log(`  Add to constants.ts: export const USDC_MINT = new PublicKey("${usdcMint.toBase58()}");`);
 }
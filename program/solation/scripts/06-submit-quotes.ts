@@ -6,6 +6,9 @@ import { getMarketMakerPDA, getAssetConfigPDA, getQuotePDA } from "./helpers/pda
 import { SOL_MINT, MOCK_PRICES, DECIMALS } from "./helpers/constants";
 import { generateStrikePrices, calculatePremium } from "./helpers/mocks";
 
+// How long each quote stays fillable at its premium
+const QUOTE_VALIDITY_SECONDS = 300;
+
 /**
  * Script 06: Submit Quotes
  *
@@ -13,6 +16,8 @@ import { generateStrikePrices, calculatePremium } from "./helpers/mocks";
  * Market makers provide pricing for different strike prices and expiries.
  *
  * IMPORTANT: Update USDC_MINT in constants.ts with the actual mint from script 02!
+ * Strikes must sit inside the asset's band around the Pyth spot price, so a
+ * posted SOL/USD PriceUpdateV2 account is required.
  */
 async function main() {
   console.log("📝 Submitting Quotes...\n");
@@ -27,15 +32,17 @@ async function main() {
   // Get USDC mint from command line
   const args = process.argv.slice(2);
   const usdcMintInput = args[0];
+  const priceUpdateInput = args[1];
 
-  if (!usdcMintInput) {
-    console.error("❌ ERROR: USDC mint not provided!");
-    console.error("Usage: npm run init:06 -- <USDC_MINT_ADDRESS>");
+  if (!usdcMintInput || !priceUpdateInput) {
+    console.error("❌ ERROR: USDC mint or price update account not provided!");
+    console.error("Usage: npm run init:06 -- <USDC_MINT_ADDRESS> <SOL_PRICE_UPDATE_ACCOUNT>");
     console.error("\nGet the USDC mint address from the output of script 02");
     process.exit(1);
   }
 
   const usdcMint = new PublicKey(usdcMintInput);
+  const priceUpdate = new PublicKey(priceUpdateInput);
   const [solAssetConfig] = getAssetConfigPDA(SOL_MINT);
 
   console.log("Configuration:");
@@ -64,6 +71,7 @@ async function main() {
       SOL_MINT,
       usdcMint,
       solAssetConfig,
+      priceUpdate,
       "CoveredCall",
       expiry,
       spotPrice
@@ -80,6 +88,7 @@ async function main() {
       SOL_MINT,
       usdcMint,
       solAssetConfig,
+      priceUpdate,
       "CashSecuredPut",
       expiry,
       spotPrice
@@ -96,6 +105,7 @@ async function submitQuote(
   assetMint: PublicKey,
   quoteMint: PublicKey,
   assetConfig: PublicKey,
+  priceUpdate: PublicKey,
   strategy: "CoveredCall" | "CashSecuredPut",
   expiry: { days: number; timestamp: number },
   spotPrice: number
@@ -117,7 +127,7 @@ async function submitQuote(
 
     // Convert to program format with proper decimals
     return {
-      strikePrice: new anchor.BN(strikePrice * 100000000), // 8 decimals
+      strikePrice: new anchor.BN(strikePrice * Math.pow(10, DECIMALS.USDC)), // Quote mint units
       premiumPerContract: new anchor.BN(premium * Math.pow(10, DECIMALS.USDC)),
       availableContracts: new anchor.BN(1000), // 1000 contracts available
     };
@@ -125,19 +135,18 @@ async function submitQuote(
 
   // Create the strategy enum properly for Anchor
   let strategyEnum: any;
-  let strategyIndex: number;
   if (strategy === "CoveredCall") {
     strategyEnum = { coveredCall: {} };
-    strategyIndex = 0;
   } else {
     strategyEnum = { cashSecuredPut: {} };
-    strategyIndex = 1;
   }
 
-  // Derive the quote PDA
-  const [quote] = getQuotePDA(marketMaker, assetMint, strategyIndex, expiry.timestamp);
+  // Derive the quote PDA from the market maker's next quote id
+  const marketMakerAccount = await program.account.marketMaker.fetch(marketMaker);
+  const quoteId = BigInt(marketMakerAccount.nextQuoteId.toString());
+  const [quote] = getQuotePDA(marketMaker, quoteId);
 
-  console.log(`  Quote PDA: ${quote.toBase58()}`);
+  console.log(`  Quote PDA: ${quote.toBase58()} (id ${quoteId})`);
   console.log(`  Number of strikes: ${strikes.length}`);
 
   try {
@@ -147,12 +156,14 @@ async function submitQuote(
         quoteMint,
         strategyEnum,
         strikes,
-        new anchor.BN(expiry.timestamp),
+        new anchor.BN(Math.floor(Date.now() / 1000) + QUOTE_VALIDITY_SECONDS), // Good-till
+        new anchor.BN(expiry.timestamp), // Option expiry
         new anchor.BN(0.1 * LAMPORTS_PER_SOL), // Min size: 0.1 SOL
         new anchor.BN(100 * LAMPORTS_PER_SOL) // Max size: 100 SOL
       )
       .accountsPartial({
         quote,
+        priceUpdate,
         owner,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -166,7 +177,8 @@ async function submitQuote(
     console.log(`  Verification:`);
     console.log(`    Market Maker: ${quoteAccount.marketMaker.toBase58()}`);
     console.log(`    Active: ${quoteAccount.active}`);
-    console.log(`    Expiry: ${new Date(quoteAccount.expiryTimestamp.toNumber() * 1000).toISOString()}`);
+    console.log(`    Valid Until: ${new Date(quoteAccount.validUntil.toNumber() * 1000).toISOString()}`);
+    console.log(`    Option Expiry: ${new Date(quoteAccount.optionExpiry.toNumber() * 1000).toISOString()}`);
     console.log(`    Min Size: ${quoteAccount.minSize.toNumber() / LAMPORTS_PER_SOL} SOL`);
     console.log(`    Max Size: ${quoteAccount.maxSize.toNumber() / LAMPORTS_PER_SOL} SOL`);
     console.log(`    Number of Strikes: ${quoteAccount.strikes.length}`);
@@ -31,6 +31,8 @@ export const DECIMALS = {
 
 // Protocol parameters
 export const PROTOCOL_FEE_BPS = 30; // 0.3% protocol fee
+export const MAX_PROTOCOL_FEE_BPS = 100; // Fee can never be raised above 1%
+export const TIMELOCK_DELAY_SECONDS = 86400; // Fee and asset changes wait 1 day
 
 // Strike price parameters (basis points relative to spot)
 export const STRIKE_PARAMS = {
@@ -44,6 +46,13 @@ export const EXPIRY_PARAMS = {
   MAX_EXPIRY_SECONDS: 2592000, // 30 days
 };
 
+// Oracle parameters
+export const ORACLE_PARAMS = {
+  MAX_CONFIDENCE_BPS: 200, // Reject prices whose confidence exceeds 2% of price
+  SETTLEMENT_WINDOW_SECONDS: 60, // Settlement price must be published within 60s of expiry
+  MAX_PRICE_AGE_SECONDS: 60, // Quotes are checked against prices at most 60s old
+};
+
 // Liquidity amounts for initial deposit
 export const INITIAL_LIQUIDITY = {
   SOL: 100, // 100 SOL
@@ -21,6 +21,16 @@ export function getAssetConfigPDA(assetMint: PublicKey): [PublicKey, number] {
   );
 }
 
+/**
+ * Derive the user account PDA (numbers the user's requests and positions)
+ */
+export function getUserAccountPDA(user: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user_account"), user.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
 /**
  * Derive the market maker PDA for a given owner
  */
@@ -116,28 +126,17 @@ export function getPositionMMVaultPDA(
 
 /**
  * Derive the quote PDA
- * Strategy: 0 = CoveredCall, 1 = CashSecuredPut
+ * Quote ids come from the market maker's next_quote_id counter
  */
 export function getQuotePDA(
   marketMaker: PublicKey,
-  assetMint: PublicKey,
-  strategy: number,
-  expiryTimestamp: number
+  quoteId: number | bigint
 ): [PublicKey, number] {
-  const strategyBuffer = Buffer.alloc(1);
-  strategyBuffer.writeUInt8(strategy);
-
-  const expiryBuffer = Buffer.alloc(8);
-  expiryBuffer.writeBigInt64LE(BigInt(expiryTimestamp));
+  const quoteIdBuffer = Buffer.alloc(8);
+  quoteIdBuffer.writeBigUInt64LE(BigInt(quoteId));
 
   return PublicKey.findProgramAddressSync(
-    [
-      Buffer.from("quote"),
-      marketMaker.toBuffer(),
-      assetMint.toBuffer(),
-      strategyBuffer,
-      expiryBuffer,
-    ],
+    [Buffer.from("quote"), marketMaker.toBuffer(), quoteIdBuffer],
     PROGRAM_ID
   );
 }
@@ -19,6 +19,13 @@ export function getAssetConfigPDA(assetMint: PublicKey): [PublicKey, number] {
   );
 }
 
+export function getUserAccountPDA(user: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user_account"), user.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
 export function getMarketMakerPDA(owner: PublicKey): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
     [Buffer.from("market_maker"), owner.toBuffer()],
@@ -88,3 +95,36 @@ export function getPositionMMVaultPDA(position: PublicKey): [PublicKey, number]
     PROGRAM_ID
   );
 }
+
+export function getPositionRequestPDA(
+  user: PublicKey,
+  requestId: bigint
+): [PublicKey, number] {
+  const requestIdBuffer = Buffer.alloc(8);
+  requestIdBuffer.writeBigUInt64LE(requestId);
+
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("position_request"), user.toBuffer(), requestIdBuffer],
+    PROGRAM_ID
+  );
+}
+
+export function getRequestEscrowPDA(request: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("request_escrow"), request.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
+export function getSettlementPricePDA(
+  assetMint: PublicKey,
+  expiryTimestamp: bigint
+): [PublicKey, number] {
+  const expiryBuffer = Buffer.alloc(8);
+  expiryBuffer.writeBigInt64LE(expiryTimestamp);
+
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("settlement_price"), assetMint.toBuffer(), expiryBuffer],
+    PROGRAM_ID
+  );
+}
@@ -1,8 +1,7 @@
 import { Program, AnchorProvider, Idl } from "@coral-xyz/anchor";
-import { Connection, PublicKey } from "@solana/web3.js";
+import { Connection } from "@solana/web3.js";
 import { AnchorWallet } from "@solana/wallet-adapter-react";
 import IDL from "./idl/solation.json";
-import { PROGRAM_ID } from "../config/constants";
 
 export type SolationProgram = Program<Idl>;
 
@@ -17,7 +16,7 @@ export function getProgram(
     commitment: "confirmed",
   });
 
-  return new Program(IDL as Idl, PROGRAM_ID, provider);
+  return new Program(IDL as Idl, provider);
 }
 
 /**
@@ -32,5 +31,5 @@ export function getProgramReadonly(connection: Connection): SolationProgram {
     }
   );
 
-  return new Program(IDL as Idl, PROGRAM_ID, provider);
+  return new Program(IDL as Idl, provider);
 }
@@ -129,14 +129,9 @@ export const DashboardPage: React.FC = () => {
         return;
       }
 
-      const positionAccount = position.account;
       const tx = await settlePosition({
         program,
-        userPublicKey: publicKey,
         positionPublicKey: position.publicKey,
-        assetMint: positionAccount.assetMint,
-        quoteMint: positionAccount.quoteMint,
-        positionId: positionAccount.positionId,
       });
 
       alert(`✅ Position settled! TX: ${tx.slice(0, 8)}...${tx.slice(-8)}`);
@@ -159,7 +154,7 @@ export const DashboardPage: React.FC = () => {
       const asset = assetMintStr === SOL_MINT.toBase58() ? 'SOL' : 'USDC';
       const strategy = account.strategy.coveredCall !== undefined ? 'covered-call' : 'cash-secured-put';
       const amount = account.contractSize.toNumber() / LAMPORTS_PER_SOL;
-      const strikePrice = account.strikePrice.toNumber() / 100000000;
+      const strikePrice = account.strikePrice.toNumber() / 1_000_000;
       const premiumEarned = account.premiumPaid.toNumber() / 1_000_000;
       const expirationDate = new Date(account.expiryTimestamp.toNumber() * 1000).toLocaleDateString();
       const createdAt = new Date(account.createdAt.toNumber() * 1000).toLocaleDateString();
@@ -13,9 +13,9 @@ import {
 } from '../utils/assetConfig';
 import { AssetSymbol, Strategy, StrikePrice, ExpirationDate } from '../types';
 import { getProgram } from '../anchor/setup';
-import { createPosition, getNextPositionId } from '../services/positions';
+import { requestPosition } from '../services/positions';
 import { fetchQuotesForAsset, getBestQuoteForStrike } from '../services/quotes';
-import { SOL_MINT, USDC_MINT } from '../config/constants';
+import { SOL_MINT } from '../config/constants';
 
 export const TradingPage: React.FC = () => {
   const { assetSymbol } = useParams<{ assetSymbol: string }>();
@@ -187,49 +187,42 @@ export const TradingPage: React.FC = () => {
         return;
       }
 
-      // Find a quote with the selected strike price (convert to 8 decimals)
-      const strikePriceInDecimals = Math.floor(selectedStrike.price * 100000000);
+      // Find a quote with the selected strike price (convert to USDC's 6 decimals)
+      const strikePriceInDecimals = Math.floor(selectedStrike.price * 1_000_000);
       const bestQuote = getBestQuoteForStrike(quotes, strikePriceInDecimals);
 
       if (!bestQuote) {
-        alert(`No quote found for strike price $${selectedStrike.price}. Available strikes: ${quotes[0]?.strikes.map(s => s.strikePrice / 100000000).join(', ')}`);
+        alert(`No quote found for strike price $${selectedStrike.price}. Available strikes: ${quotes[0]?.strikes.map(s => s.strikePrice / 1_000_000).join(', ')}`);
         setIsCreatingPosition(false);
         return;
       }
 
       console.log('Using quote:', bestQuote.quote.publicKey.toBase58());
 
-      // Get next position ID for this user
-      const positionId = await getNextPositionId(program, publicKey);
-      console.log('Creating position with ID:', positionId.toString());
-
       // Calculate contract size based on asset
       const contractSize = strategy === 'cash-secured-put'
         ? Math.floor((parseFloat(amount) / selectedStrike.price) * LAMPORTS_PER_SOL)
         : Math.floor(parseFloat(amount) * LAMPORTS_PER_SOL);
 
-      // Create the position
-      const tx = await createPosition({
+      // Request the position; it opens once the market maker confirms
+      const tx = await requestPosition({
         program,
         userPublicKey: publicKey,
-        assetMint,
-        quoteMint: USDC_MINT,
         quoteAddress: bestQuote.quote.publicKey,
         strikePrice: strikePriceInDecimals,
         contractSize,
-        positionId,
       });
 
       setTransactionSignature(tx);
 
-      // Calculate premium earned
+      // Premium paid once the market maker confirms
       const premiumEarned = strategy === 'cash-secured-put'
         ? (parseFloat(amount) / selectedStrike.price) * selectedStrike.premium
         : parseFloat(amount) * selectedStrike.premium;
 
       alert(
-        `✅ Position created successfully!\n\n` +
-        `Premium earned: ${formatCurrency(premiumEarned)} USDC\n` +
+        `✅ Position requested!\n\n` +
+        `Premium on confirmation: ${formatCurrency(premiumEarned)} USDC\n` +
         `Transaction: ${tx.slice(0, 8)}...${tx.slice(-8)}\n\n` +
         `View in Dashboard or on Solscan`
       );
@@ -1,126 +1,137 @@
-import { PublicKey, Transaction, Keypair } from "@solana/web3.js";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
 import { getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import BN from "bn.js";
 import { SolationProgram } from "../anchor/setup";
 import {
   getGlobalStatePDA,
-  getMarketMakerPDA,
   getAssetConfigPDA,
-  getPositionPDA,
-  getPositionUserVaultPDA,
-  getPositionMMVaultPDA,
-  getMMVaultPDA,
-  getVaultAuthorityPDA,
+  getUserAccountPDA,
+  getPositionRequestPDA,
+  getRequestEscrowPDA,
 } from "../anchor/pdas";
-import { MARKET_MAKER_OWNER } from "../config/constants";
 
-export interface CreatePositionParams {
+export interface RequestPositionParams {
   program: SolationProgram;
   userPublicKey: PublicKey;
-  assetMint: PublicKey;
-  quoteMint: PublicKey; // USDC
-  quoteAddress: PublicKey; // The quote account to use
-  strikePrice: number; // in base units (e.g., 180_00000000 for $180)
+  quoteAddress: PublicKey; // The quote account to take
+  strikePrice: number; // in quote mint units (e.g., 180_000000 for $180 USDC)
   contractSize: number; // in lamports/smallest unit
-  positionId: bigint; // unique position ID for the user
 }
 
 /**
- * Create a new options position
- * This executes the create_position instruction
+ * Collateral the user escrows: the asset for covered calls, the quote mint for puts
  */
-export async function createPosition(params: CreatePositionParams): Promise<string> {
+function userCollateralMint(account: any): PublicKey {
+  return account.strategy.cashSecuredPut !== undefined
+    ? account.quoteMint
+    : account.assetMint;
+}
+
+/**
+ * Fetch the user's account, or null if it hasn't been created yet
+ */
+export async function fetchUserAccount(
+  program: SolationProgram,
+  userPublicKey: PublicKey
+) {
+  const [userAccount] = getUserAccountPDA(userPublicKey);
+  return program.account.userAccount.fetchNullable(userAccount);
+}
+
+/**
+ * Request a position against a market maker quote
+ * Escrows the user's collateral; the position opens once the market maker confirms
+ * Creates the user account first if this is the user's first request
+ */
+export async function requestPosition(params: RequestPositionParams): Promise<string> {
   const {
     program,
     userPublicKey,
-    assetMint,
-    quoteMint,
     quoteAddress,
     strikePrice,
     contractSize,
-    positionId,
   } = params;
 
+  const quote = (await program.account.quote.fetch(quoteAddress)) as any;
+  const collateralMint = userCollateralMint(quote);
+
   // Derive PDAs
   const [globalState] = getGlobalStatePDA();
-  const [marketMaker] = getMarketMakerPDA(MARKET_MAKER_OWNER);
-  const [assetConfig] = getAssetConfigPDA(assetMint);
-  const [position] = getPositionPDA(userPublicKey, positionId);
-  const [positionUserVault] = getPositionUserVaultPDA(position);
-  const [positionUserVaultAuthority] = getPositionPDA(userPublicKey, positionId); // Same as position
-  const [positionMMVault] = getPositionMMVaultPDA(position);
-  const [positionMMVaultAuthority] = getPositionPDA(userPublicKey, positionId); // Same as position
-  const [mmVault] = getMMVaultPDA(marketMaker, quoteMint); // Premium vault (USDC)
-  const [mmPremiumVault] = getMMVaultPDA(marketMaker, quoteMint);
-  const [vaultAuthority] = getVaultAuthorityPDA(marketMaker, quoteMint);
+  const [assetConfig] = getAssetConfigPDA(quote.assetMint);
+  const [userAccount] = getUserAccountPDA(userPublicKey);
 
-  // Get MM vault state to retrieve token account addresses
-  const mmVaultState = await program.account.marketMakerVault.fetch(mmVault);
-  const mmVaultTokenAccount = mmVaultState.vaultTokenAccount;
+  // The request is numbered by the user account's next_request_id
+  const existing = (await fetchUserAccount(program, userPublicKey)) as any;
+  const requestId = existing ? BigInt(existing.nextRequestId.toString()) : 0n;
+  const [positionRequest] = getPositionRequestPDA(userPublicKey, requestId);
+  const [requestEscrow] = getRequestEscrowPDA(positionRequest);
 
-  const mmPremiumVaultState = await program.account.marketMakerVault.fetch(mmPremiumVault);
-  const mmPremiumVaultTokenAccount = mmPremiumVaultState.vaultTokenAccount;
-
-  // Get user token accounts
-  const userAssetTokenAccount = await getAssociatedTokenAddress(
-    assetMint,
+  const userTokenAccount = await getAssociatedTokenAddress(
+    collateralMint,
     userPublicKey
   );
 
-  const userPremiumAccount = await getAssociatedTokenAddress(
-    quoteMint,
-    userPublicKey
-  );
+  const preInstructions = existing
+    ? []
+    : [
+        await program.methods
+          .initializeUserAccount()
+          .accountsPartial({
+            userAccount,
+            user: userPublicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .instruction(),
+      ];
 
-  console.log("Creating position with accounts:");
-  console.log("  Global State:", globalState.toBase58());
-  console.log("  Market Maker:", marketMaker.toBase58());
-  console.log("  Quote:", quoteAddress.toBase58());
-  console.log("  Asset Config:", assetConfig.toBase58());
-  console.log("  Position:", position.toBase58());
-  console.log("  User:", userPublicKey.toBase58());
+  return program.methods
+    .requestPosition(new BN(strikePrice), new BN(contractSize))
+    .accountsPartial({
+      globalState,
+      marketMaker: quote.marketMaker,
+      quote: quoteAddress,
+      assetConfig,
+      userAccount,
+      positionRequest,
+      requestEscrow,
+      userTokenAccount,
+      collateralMint,
+      user: userPublicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .preInstructions(preInstructions)
+    .rpc();
+}
 
-  try {
-    const tx = await program.methods
-      .createPosition(
-        new BN(positionId.toString()),
-        new BN(strikePrice),
-        new BN(contractSize)
-      )
-      .accounts({
-        globalState,
-        marketMaker,
-        quote: quoteAddress,
-        assetConfig,
-        position,
-        positionUserVault,
-        positionUserVaultAuthority,
-        positionMmVault: positionMMVault,
-        positionMmVaultAuthority: positionMMVaultAuthority,
-        mmVault,
-        mmVaultTokenAccount,
-        mmVaultAuthority: vaultAuthority,
-        mmPremiumVault,
-        mmPremiumVaultTokenAccount,
-        mmPremiumVaultAuthority: vaultAuthority,
-        userTokenAccount: userAssetTokenAccount,
-        userPremiumAccount,
-        userAssetMint: assetMint,
-        mmAssetMint: quoteMint,
-        premiumMint: quoteMint,
-        user: userPublicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        systemProgram: PublicKey.default, // SystemProgram.programId
-      })
-      .rpc();
+/**
+ * Cancel the user's own pending request and refund the escrow
+ */
+export async function cancelRequest(
+  program: SolationProgram,
+  userPublicKey: PublicKey,
+  positionRequest: PublicKey
+): Promise<string> {
+  const request = (await program.account.positionRequest.fetch(
+    positionRequest
+  )) as any;
+  const [requestEscrow] = getRequestEscrowPDA(positionRequest);
+  const userTokenAccount = await getAssociatedTokenAddress(
+    userCollateralMint(request),
+    userPublicKey
+  );
 
-    console.log("✅ Position created! Transaction:", tx);
-    return tx;
-  } catch (error) {
-    console.error("❌ Error creating position:");
-    console.error(error);
-    throw error;
-  }
+  return program.methods
+    .cancelRequest()
+    .accountsPartial({
+      positionRequest,
+      quote: request.quote,
+      requestEscrow,
+      userTokenAccount,
+      user: userPublicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
 }
 
 /**
@@ -134,7 +145,7 @@ export async function fetchUserPositions(
     const positions = await program.account.position.all([
       {
         memcmp: {
-          offset: 8 + 8, // Discriminator (8) + position_id (8)
+          offset: 8 + 8 + 32 + 8, // Discriminator (8) + position_id (8) + request (32) + request_id (8)
           bytes: userPublicKey.toBase58(),
         },
       },
@@ -167,15 +178,3 @@ export async function fetchPosition(
     return null;
   }
 }
-
-/**
- * Get the next available position ID for a user
- * This counts existing positions and returns the next sequential ID
- */
-export async function getNextPositionId(
-  program: SolationProgram,
-  userPublicKey: PublicKey
-): Promise<bigint> {
-  const positions = await fetchUserPositions(program, userPublicKey);
-  return BigInt(positions.length);
-}
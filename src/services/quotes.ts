@@ -6,6 +6,7 @@ import { MARKET_MAKER_OWNER } from "../config/constants";
 export interface Quote {
   publicKey: PublicKey;
   marketMaker: PublicKey;
+  quoteId: number;
   assetMint: PublicKey;
   quoteMint: PublicKey;
   strategy: "CoveredCall" | "CashSecuredPut";
@@ -14,11 +15,13 @@ export interface Quote {
     premiumPerContract: number;
     availableContracts: number;
   }>;
-  expiryTimestamp: number;
+  validUntil: number; // quote can be taken until this time
+  optionExpiry: number; // expiry of positions opened from this quote
   minSize: number;
   maxSize: number;
   lastUpdated: number;
   active: boolean;
+  pendingRequests: number; // open requests; the quote can't close while > 0
 }
 
 /**
@@ -52,6 +55,7 @@ export async function fetchMarketMakerQuotes(
       return {
         publicKey: q.publicKey,
         marketMaker: account.marketMaker,
+        quoteId: account.quoteId.toNumber(),
         assetMint: account.assetMint,
         quoteMint: account.quoteMint,
         strategy,
@@ -60,11 +64,13 @@ export async function fetchMarketMakerQuotes(
           premiumPerContract: s.premiumPerContract.toNumber(),
           availableContracts: s.availableContracts.toNumber(),
         })),
-        expiryTimestamp: account.expiryTimestamp.toNumber(),
+        validUntil: account.validUntil.toNumber(),
+        optionExpiry: account.optionExpiry.toNumber(),
         minSize: account.minSize.toNumber(),
         maxSize: account.maxSize.toNumber(),
         lastUpdated: account.lastUpdated.toNumber(),
         active: account.active,
+        pendingRequests: account.pendingRequests,
       };
     });
   } catch (error) {
@@ -89,7 +95,7 @@ export async function fetchQuotesForAsset(
       q.assetMint.equals(assetMint) &&
       q.strategy === strategy &&
       q.active &&
-      q.expiryTimestamp > Date.now() / 1000 // Not expired
+      q.validUntil > Date.now() / 1000 // Still takeable
   );
 }
 
@@ -120,9 +126,9 @@ export function groupQuotesByExpiry(quotes: Quote[]): Map<number, Quote[]> {
   const grouped = new Map<number, Quote[]>();
 
   for (const quote of quotes) {
-    const existing = grouped.get(quote.expiryTimestamp) || [];
+    const existing = grouped.get(quote.optionExpiry) || [];
     existing.push(quote);
-    grouped.set(quote.expiryTimestamp, existing);
+    grouped.set(quote.optionExpiry, existing);
   }
 
   return grouped;
@@ -1,118 +1,102 @@
-import { PublicKey, Keypair } from "@solana/web3.js";
+import { PublicKey } from "@solana/web3.js";
 import { getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { SolationProgram } from "../anchor/setup";
 import {
+  getGlobalStatePDA,
   getAssetConfigPDA,
-  getMarketMakerPDA,
   getMMVaultPDA,
-  getVaultAuthorityPDA,
-  getPositionUserVaultPDA,
-  getPositionMMVaultPDA,
-  getPositionPDA,
+  getUserAccountPDA,
+  getSettlementPricePDA,
 } from "../anchor/pdas";
-import { MARKET_MAKER_OWNER } from "../config/constants";
 
 export interface SettlePositionParams {
   program: SolationProgram;
-  userPublicKey: PublicKey;
   positionPublicKey: PublicKey;
-  assetMint: PublicKey;
-  quoteMint: PublicKey;
-  positionId: bigint;
 }
 
 /**
- * Settle an expired position
- * Uses a mock Pyth price update account for testing
+ * Collateral the MM locks: the quote mint for covered calls, the asset for puts
  */
-export async function settlePosition(params: SettlePositionParams): Promise<string> {
-  const {
-    program,
-    userPublicKey,
-    positionPublicKey,
-    assetMint,
-    quoteMint,
-    positionId,
-  } = params;
+function mmCollateralMint(position: any): PublicKey {
+  return position.strategy.cashSecuredPut !== undefined
+    ? position.assetMint
+    : position.quoteMint;
+}
 
-  // Derive PDAs
-  const [assetConfig] = getAssetConfigPDA(assetMint);
-  const [marketMaker] = getMarketMakerPDA(MARKET_MAKER_OWNER);
-  const [positionUserVault] = getPositionUserVaultPDA(positionPublicKey);
-  const [positionMMVault] = getPositionMMVaultPDA(positionPublicKey);
-  const [position] = getPositionPDA(userPublicKey, positionId);
-  const [mmVault] = getMMVaultPDA(marketMaker, assetMint);
-  const [vaultAuthority] = getVaultAuthorityPDA(marketMaker, assetMint);
-
-  // Get user destination token accounts
-  const userDestination = await getAssociatedTokenAddress(
-    assetMint,
-    userPublicKey
+/**
+ * Settlement price record for a position's asset and expiry
+ */
+function getPositionSettlementPricePDA(position: any): PublicKey {
+  const [record] = getSettlementPricePDA(
+    position.assetMint,
+    BigInt(position.expiryTimestamp.toString())
   );
+  return record;
+}
 
-  // Get MM vault state to retrieve token account
-  const mmVaultState = await program.account.marketMakerVault.fetch(mmVault);
-  const mmDestination = mmVaultState.vaultTokenAccount;
-
-  // Create a mock Pyth price update account
-  // In production, you would use actual Pyth price feeds
-  const mockPriceUpdate = createMockPythPriceAccount();
+/**
+ * Settle an expired position against the published settlement price for its expiry
+ * Payouts go to the position user's and the market maker owner's token accounts
+ */
+export async function settlePosition(params: SettlePositionParams): Promise<string> {
+  const { program, positionPublicKey } = params;
 
-  console.log("Settling position with accounts:");
-  console.log("  Position:", positionPublicKey.toBase58());
-  console.log("  Asset Config:", assetConfig.toBase58());
-  console.log("  Market Maker:", marketMaker.toBase58());
-  console.log("  User:", userPublicKey.toBase58());
+  const position = (await program.account.position.fetch(positionPublicKey)) as any;
+  const marketMaker = (await program.account.marketMaker.fetch(
+    position.marketMaker
+  )) as any;
 
-  try {
-    const tx = await program.methods
-      .settlePosition()
-      .accounts({
-        position: positionPublicKey,
-        assetConfig,
-        marketMaker,
-        positionUserVault,
-        positionMmVault: positionMMVault,
-        positionVaultAuthority: position, // Same as position PDA
-        mmVault,
-        userDestination,
-        mmDestination,
-        priceUpdate: mockPriceUpdate,
-        tokenProgram: TOKEN_PROGRAM_ID,
-      })
-      .rpc();
-
-    console.log("✅ Position settled! Transaction:", tx);
-    return tx;
-  } catch (error) {
-    console.error("❌ Error settling position:");
-    console.error(error);
-    throw error;
-  }
+  // Derive PDAs
+  const [globalState] = getGlobalStatePDA();
+  const [assetConfig] = getAssetConfigPDA(position.assetMint);
+  const [mmVault] = getMMVaultPDA(position.marketMaker, mmCollateralMint(position));
+  const [userAccount] = getUserAccountPDA(position.user);
+  const settlementPriceRecord = getPositionSettlementPricePDA(position);
+
+  // Destination accounts for both sides, in both mints
+  const userAssetAccount = await getAssociatedTokenAddress(position.assetMint, position.user);
+  const userQuoteAccount = await getAssociatedTokenAddress(position.quoteMint, position.user);
+  const mmAssetAccount = await getAssociatedTokenAddress(position.assetMint, marketMaker.owner);
+  const mmQuoteAccount = await getAssociatedTokenAddress(position.quoteMint, marketMaker.owner);
+
+  return program.methods
+    .settlePosition()
+    .accountsPartial({
+      globalState,
+      position: positionPublicKey,
+      assetConfig,
+      marketMaker: position.marketMaker,
+      positionUserVault: position.userVault,
+      positionMmVault: position.mmVaultLocked,
+      positionVaultAuthority: positionPublicKey, // Position PDA owns both vaults
+      mmVault,
+      userAccount,
+      userAssetAccount,
+      userQuoteAccount,
+      mmAssetAccount,
+      mmQuoteAccount,
+      settlementPriceRecord,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
 }
 
 /**
- * Create a mock Pyth price update account
- * In production, you would use actual Pyth price feeds
- *
- * For localnet testing, this returns a dummy keypair
- * The actual price checking would need to be disabled in the contract
- * or you'd need to set up a local Pyth oracle
+ * Whether the settlement price for a position's expiry has been published
  */
-function createMockPythPriceAccount(): PublicKey {
-  // For now, return a dummy public key
-  // In a real implementation, you would:
-  // 1. Run a local Pyth oracle
-  // 2. Or modify the contract to accept mock prices for testing
-  // 3. Or use Pyth's localnet deployment
-
-  // This is a placeholder - the contract will need to handle this appropriately
-  return Keypair.generate().publicKey;
+async function isSettlementPricePublished(
+  program: SolationProgram,
+  position: any
+): Promise<boolean> {
+  const record = (await program.account.settlementPriceRecord.fetchNullable(
+    getPositionSettlementPricePDA(position)
+  )) as any;
+  return record !== null && record.settlementPrice !== null;
 }
 
 /**
  * Check if a position can be settled
- * Returns true if the position is expired and active
+ * Returns true if the position is active, expired and its settlement price is published
  */
 export async function canSettlePosition(
   program: SolationProgram,
@@ -127,7 +111,7 @@ export async function canSettlePosition(
     const isActive = status.active !== undefined;
     const isExpired = (position as any).expiryTimestamp.toNumber() <= now;
 
-    return isActive && isExpired;
+    return isActive && isExpired && (await isSettlementPricePublished(program, position));
   } catch (error) {
     console.error("Error checking position settlement status:");
     console.error(error);
@@ -146,7 +130,7 @@ export async function getSettleablePositions(
     const positions = await program.account.position.all([
       {
         memcmp: {
-          offset: 8 + 8, // Discriminator (8) + position_id (8)
+          offset: 8 + 8 + 32 + 8, // Discriminator (8) + position_id (8) + request (32) + request_id (8)
           bytes: userPublicKey.toBase58(),
         },
       },
@@ -161,7 +145,7 @@ export async function getSettleablePositions(
       const isActive = status.active !== undefined;
       const isExpired = account.expiryTimestamp.toNumber() <= now;
 
-      if (isActive && isExpired) {
+      if (isActive && isExpired && (await isSettlementPricePublished(program, account))) {
         settleable.push(p.publicKey);
       }
     }